@@ -0,0 +1,169 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::Heap;
+
+/// An associative way of combining values, with an identity element
+pub trait Monoid {
+	type Value: Clone;
+	fn identity() -> Self::Value;
+	/// Must be associative, but need not be commutative: `a` always comes before `b` in
+	/// depth-first pre-order
+	fn combine(a: &Self::Value, b: &Self::Value) -> Self::Value;
+}
+
+/// Keeps the combined value of every subtree of a heap, so that both changing one node's
+/// value and querying a subtree take O(log n) time.
+/// Nodes are laid out in depth-first pre-order, where every subtree is a contiguous run,
+/// and the runs are combined with a segment tree.
+/// The aggregate reflects the shape the heap had when it was built; rebuild it after
+/// inserting, removing or moving nodes.
+#[derive(Clone, Debug)]
+pub struct SubtreeAggregate<M: Monoid> {
+	// Pre-order position of each valid node
+	position: Vec<Option<usize>>,
+	// Subtree size of the node at each position
+	sizes: Vec<usize>,
+	// The value at position `p` is kept in `tree[sizes.len() + p]`, and `tree[j]` combines
+	// `tree[2 * j]` and `tree[2 * j + 1]` below that
+	tree: Vec<M::Value>,
+}
+
+impl<M: Monoid> SubtreeAggregate<M> {
+	/// Takes the value of each node from `measure`
+	pub fn build<T, F: Fn(&T) -> M::Value>(heap: &Heap<T>, measure: F) -> Self {
+		let mut position = vec![None; heap.inner.len()];
+		let (mut sizes, mut values) = (Vec::new(), Vec::new());
+		for (i, value) in heap.iter_dfs(0) {
+			position[i] = Some(values.len());
+			sizes.push(heap.subtree_size(i));
+			values.push(measure(value));
+		}
+		let mut tree = vec![M::identity(); values.len()];
+		tree.append(&mut values);
+		for j in (1..sizes.len()).rev() {
+			tree[j] = M::combine(&tree[2 * j], &tree[2 * j + 1]);
+		}
+		Self { position, sizes, tree }
+	}
+
+	fn position(&self, index: usize) -> usize {
+		self.position.get(index).copied().flatten()
+			.expect("Heap: Error: Tried to use a node the aggregate does not know")
+	}
+
+	/// Returns the value of the node at `index` alone.
+	/// Panics if `index` was not a valid node when the aggregate was built
+	pub fn get(&self, index: usize) -> &M::Value {
+		&self.tree[self.sizes.len() + self.position(index)]
+	}
+
+	/// Panics if `index` was not a valid node when the aggregate was built
+	pub fn update(&mut self, index: usize, value: M::Value) {
+		let mut j = self.sizes.len() + self.position(index);
+		self.tree[j] = value;
+		while j > 1 {
+			j /= 2;
+			self.tree[j] = M::combine(&self.tree[2 * j], &self.tree[2 * j + 1]);
+		}
+	}
+
+	/// Combines the values of every node in the subtree rooted at `index`.
+	/// Panics if `index` was not a valid node when the aggregate was built
+	pub fn query(&self, index: usize) -> M::Value {
+		let start = self.position(index);
+		let (mut low, mut high) = (self.sizes.len() + start, self.sizes.len() + start + self.sizes[start]);
+		// Blocks are taken from both ends inwards, so they are combined from the outside in
+		let (mut left, mut right) = (M::identity(), M::identity());
+		while low < high {
+			if low % 2 == 1 {
+				left = M::combine(&left, &self.tree[low]);
+				low += 1;
+			}
+			if high % 2 == 1 {
+				high -= 1;
+				right = M::combine(&self.tree[high], &right);
+			}
+			low /= 2;
+			high /= 2;
+		}
+		M::combine(&left, &right)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::format;
+	use alloc::string::String;
+
+	use super::{Monoid, SubtreeAggregate};
+	use crate::{tree, Heap};
+
+	struct Sum;
+
+	impl Monoid for Sum {
+		type Value = u64;
+		fn identity() -> u64 {
+			0
+		}
+		fn combine(a: &u64, b: &u64) -> u64 {
+			a + b
+		}
+	}
+
+	struct Concat;
+
+	impl Monoid for Concat {
+		type Value = String;
+		fn identity() -> String {
+			String::new()
+		}
+		fn combine(a: &String, b: &String) -> String {
+			a.clone() + b
+		}
+	}
+
+	#[test]
+	fn subtree_sum_test() {
+		let mut files = tree!(("/", 0) => [("docs", 0) => [("a.txt", 10), ("b.txt", 20)], ("src", 0) => [("main.rs", 5)]]);
+		let mut sizes = SubtreeAggregate::<Sum>::build(&files, |&(_, size)| size);
+		assert_eq!((sizes.query(0), sizes.query(1), sizes.query(4)), (35, 30, 5));
+		files[2].1 = 100;
+		sizes.update(2, 100);
+		assert_eq!((sizes.query(0), sizes.query(1), sizes.query(2)), (125, 120, 100));
+		assert_eq!(*sizes.get(1), 0);
+	}
+
+	#[test]
+	fn subtree_order_test() {
+		let mut heap = tree!("r" => ["a" => ["c", "d" => ["e"]], "b" => ["f", "g"]]);
+		heap.remove(2);
+		let h = heap.insert("h", 5);
+		let mut names = SubtreeAggregate::<Concat>::build(&heap, |&name| String::from(name));
+		assert_eq!(names.query(0), "radebfgh");
+		assert_eq!(names.query(5), "bfgh");
+		names.update(h, String::from("H"));
+		assert_eq!(names.query(0), "radebfgH");
+		for (i, _) in heap.iter_dfs(0) {
+			let expected: String = heap.iter_dfs(i).map(|(_, &name)| if name == "h" { "H" } else { name }).collect();
+			assert_eq!(names.query(i), expected);
+		}
+	}
+
+	#[test]
+	fn subtree_many_test() {
+		let mut heap = Heap::new(0);
+		for i in 1..300 {
+			heap.insert(i, if i % 5 == 0 { i - 1 } else { (i - 1) / 2 });
+		}
+		let mut names = SubtreeAggregate::<Concat>::build(&heap, |i| format!("{i},"));
+		for i in (0..300).step_by(7) {
+			names.update(i, format!("<{i}>,"));
+		}
+		for (i, _) in heap.iter_dfs(0) {
+			let expected: String = heap.iter_dfs(i).map(|(_, &j)| if j % 7 == 0 { format!("<{j}>,") } else { format!("{j},") }).collect();
+			assert_eq!(names.query(i), expected);
+			assert_eq!(*names.get(i), expected[..expected.find(',').unwrap() + 1]);
+		}
+	}
+}
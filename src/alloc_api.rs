@@ -0,0 +1,107 @@
+use alloc::alloc::Global;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::alloc::{AllocError, Allocator, Layout};
+use core::fmt;
+use core::ptr::NonNull;
+
+use crate::{Heap, Nodes};
+
+/// The allocator backing a heap's nodes: either the global allocator,
+/// or a custom one shared by every heap split off from the heap it was given to
+#[derive(Clone, Default)]
+pub struct HeapAllocator(Option<Arc<dyn Allocator + Send + Sync>>);
+
+impl HeapAllocator {
+	pub fn new<A: Allocator + Send + Sync + 'static>(alloc: A) -> Self {
+		Self(Some(Arc::new(alloc)))
+	}
+	pub fn global() -> Self {
+		Self(None)
+	}
+}
+
+// Every clone refers to the same underlying allocator, so memory allocated through one
+// clone may be freed through any other
+unsafe impl Allocator for HeapAllocator {
+	fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+		match &self.0 {
+			Some(alloc) => alloc.allocate(layout),
+			None => Global.allocate(layout),
+		}
+	}
+	unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+		// SAFETY: The caller guarantees `ptr` was allocated by this allocator with `layout`
+		unsafe {
+			match &self.0 {
+				Some(alloc) => alloc.deallocate(ptr, layout),
+				None => Global.deallocate(ptr, layout),
+			}
+		}
+	}
+}
+
+impl fmt::Debug for HeapAllocator {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.0 {
+			Some(_) => f.write_str("HeapAllocator(custom)"),
+			None => f.write_str("HeapAllocator(global)"),
+		}
+	}
+}
+
+pub(crate) fn nodes_with_capacity<T>(capacity: usize) -> Nodes<T> {
+	Vec::with_capacity_in(capacity, HeapAllocator::global())
+}
+
+impl<T> Heap<T> {
+	/// Stores the heap's nodes in `alloc`. The free list stays in the global allocator.
+	pub fn new_in<A: Allocator + Send + Sync + 'static>(root: T, alloc: A) -> Self {
+		Self::with_capacity_in(0, root, alloc)
+	}
+	pub fn with_capacity_in<A: Allocator + Send + Sync + 'static>(capacity: usize, root: T, alloc: A) -> Self {
+		Self::with_nodes(Vec::with_capacity_in(capacity, HeapAllocator::new(alloc)), root)
+	}
+	pub fn allocator(&self) -> &HeapAllocator {
+		self.inner.allocator()
+	}
+	// Storage for a new heap that shares this heap's allocator
+	pub(crate) fn sibling_nodes<U>(&self, capacity: usize) -> Nodes<U> {
+		Vec::with_capacity_in(capacity, self.inner.allocator().clone())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use std::sync::atomic::{AtomicUsize, Ordering};
+
+	use super::*;
+
+	#[derive(Default)]
+	struct Counting(AtomicUsize);
+
+	unsafe impl Allocator for &'static Counting {
+		fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+			self.0.fetch_add(1, Ordering::Relaxed);
+			Global.allocate(layout)
+		}
+		unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+			unsafe { Global.deallocate(ptr, layout) }
+		}
+	}
+
+	#[test]
+	fn new_in_test() {
+		static COUNTING: Counting = Counting(AtomicUsize::new(0));
+		let mut heap = Heap::new_in("root", &COUNTING);
+		let a = heap.insert("a", 0);
+		heap.insert("a1", a);
+		assert!(COUNTING.0.load(Ordering::Relaxed) > 0);
+		let before = COUNTING.0.load(Ordering::Relaxed);
+		let split = heap.split_off(a);
+		assert!(COUNTING.0.load(Ordering::Relaxed) > before);
+		assert_eq!(split.len(), 2);
+		assert_eq!(format!("{:?}", split.allocator()), "HeapAllocator(custom)");
+		assert_eq!(format!("{:?}", Heap::new(0).allocator()), "HeapAllocator(global)");
+	}
+}
@@ -0,0 +1,49 @@
+use alloc::boxed::Box;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use once_cell::race::OnceBox;
+
+use crate::Heap;
+
+// The depth-first pre-order number of every slot, built by the first ancestry check after the
+// shape of the tree changes and dropped by every such change. Together with the cached subtree
+// sizes, these give each node the interval of numbers its descendants occupy.
+#[derive(Default)]
+pub(crate) struct PreOrder(OnceBox<Vec<usize>>);
+
+impl PreOrder {
+	pub(crate) fn invalidate(&mut self) {
+		self.0 = OnceBox::new();
+	}
+	pub(crate) fn is_built(&self) -> bool {
+		self.0.get().is_some()
+	}
+}
+
+// A clone has the same shape, but rebuilding is cheaper than copying an index it may not use
+impl Clone for PreOrder {
+	fn clone(&self) -> Self {
+		Self::default()
+	}
+}
+
+impl fmt::Debug for PreOrder {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("PreOrder").field(&self.is_built()).finish()
+	}
+}
+
+impl<T> Heap<T> {
+	// Builds the pre-order numbers if a change has dropped them since the last check
+	pub(crate) fn pre_order_numbers(&self) -> &[usize] {
+		self.pre_order.0.get_or_init(|| {
+			let mut numbers = vec![0; self.inner.len()];
+			for (n, (i, _)) in self.iter_dfs(0).enumerate() {
+				numbers[i] = n;
+			}
+			Box::new(numbers)
+		})
+	}
+}
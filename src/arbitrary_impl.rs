@@ -0,0 +1,55 @@
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::Heap;
+
+/// Builds the heap by inserting nodes one at a time under a parent chosen among the nodes
+/// built so far, so any input gives a valid heap numbered in insertion order without gaps
+impl<'a, T: Arbitrary<'a>> Arbitrary<'a> for Heap<T> {
+	fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+		let root = u.arbitrary()?;
+		let descendants = u.arbitrary_len::<(u32, T)>()?;
+		let mut heap = Heap::with_capacity(descendants + 1, root);
+		for _ in 0..descendants {
+			let parent = u.choose_index(heap.len())?;
+			heap.insert(u.arbitrary()?, parent);
+		}
+		Ok(heap)
+	}
+	fn size_hint(depth: usize) -> (usize, Option<usize>) {
+		(T::size_hint(depth).0, None)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use arbitrary::{Arbitrary, Unstructured};
+
+	use crate::Heap;
+
+	#[test]
+	fn arbitrary_heap_test() {
+		let mut seed = 0x2545_f491_u32;
+		for len in [0, 1, 7, 64, 1000] {
+			let bytes: Vec<u8> = (0..len).map(|_| {
+				seed ^= seed << 13;
+				seed ^= seed >> 17;
+				seed ^= seed << 5;
+				seed as u8
+			}).collect();
+			let heap = Heap::<u16>::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+			assert_eq!(heap.iter_dfs(0).count(), heap.len());
+			for (i, _) in heap.iter_dfs(0).skip(1) {
+				let parent = heap.parent_of(i).unwrap();
+				assert!(heap.children(parent).any(|c| c == i));
+				assert_eq!(heap.depth_of(i), heap.depth_of(parent) + 1);
+			}
+			if len == 1000 {
+				assert!(heap.len() > 1);
+			}
+		}
+		let empty = Heap::<u8>::arbitrary_take_rest(Unstructured::new(&[])).unwrap();
+		assert_eq!(empty.len(), 1);
+	}
+}
@@ -0,0 +1,329 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug};
+
+use crate::{fits_raw_index, Heap, HeapError, IndexMap, Key, Map, Set};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EdgeError<K> {
+	/// The key appears as the child of more than one edge, or as the child of an edge and the root
+	DuplicateChild(K),
+	/// The key's chain of parents loops back on itself without reaching the root
+	Cycle(K),
+	/// The key's chain of parents ends at a key that is never defined
+	Unreachable(K),
+}
+
+/// Builds a heap in document order, like an XML writer.
+/// Every `begin_child` opens a node under the currently open node, and `end_child` closes it.
+#[derive(Clone, Debug)]
+pub struct HeapBuilder<T> {
+	heap: Heap<T>,
+	// The currently open nodes, starting with the root
+	open: Vec<usize>,
+}
+
+impl<T> HeapBuilder<T> {
+	pub fn new(root: T) -> Self {
+		Self { heap: Heap::new(root), open: vec![0] }
+	}
+	/// Opens a new node under the currently open node
+	pub fn begin_child(&mut self, value: T) -> &mut Self {
+		let parent = *self.open.last().expect("Heap: Internal Error: The root was closed.");
+		let index = self.heap.insert(value, parent);
+		self.open.push(index);
+		self
+	}
+	/// Adds a node under the currently open node without opening it
+	pub fn child(&mut self, value: T) -> &mut Self {
+		self.begin_child(value).end_child()
+	}
+	/// Closes the currently open node.
+	/// Panics if only the root is open
+	pub fn end_child(&mut self) -> &mut Self {
+		assert!(self.open.len() > 1, "Heap: Error: Tried to close the root node");
+		self.open.pop();
+		self
+	}
+	/// The number of nodes currently open, not counting the root
+	pub fn depth(&self) -> usize {
+		self.open.len() - 1
+	}
+	/// Any nodes still open are closed
+	pub fn finish(self) -> Heap<T> {
+		self.heap
+	}
+}
+
+impl<K: Debug> fmt::Display for EdgeError<K> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::DuplicateChild(k) => write!(f, "Heap: Error: {k:?} has more than one parent"),
+			Self::Cycle(k) => write!(f, "Heap: Error: {k:?} is part of a cycle"),
+			Self::Unreachable(k) => write!(f, "Heap: Error: {k:?} is not reachable from the root"),
+		}
+	}
+}
+
+impl<K: Debug> core::error::Error for EdgeError<K> {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ParentArrayError {
+	/// The values and parents arrays have different lengths
+	LengthMismatch { values: usize, parents: usize },
+	/// No position has a parent of `None`
+	NoRoot,
+	/// More than one position has a parent of `None`
+	MultipleRoots(usize, usize),
+	/// The parent of the given position is out of range
+	InvalidParent(usize),
+	/// The given position is its own ancestor
+	Cycle(usize),
+}
+
+impl fmt::Display for ParentArrayError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::LengthMismatch { values, parents } =>
+				write!(f, "Heap: Error: Got {values} values but {parents} parents"),
+			Self::NoRoot => write!(f, "Heap: Error: No node is without a parent"),
+			Self::MultipleRoots(a, b) => write!(f, "Heap: Error: Both {a} and {b} are without a parent"),
+			Self::InvalidParent(i) => write!(f, "Heap: Error: The parent of {i} is out of range"),
+			Self::Cycle(i) => write!(f, "Heap: Error: {i} is its own ancestor"),
+		}
+	}
+}
+
+impl core::error::Error for ParentArrayError {}
+
+impl<T> Heap<T> {
+	/// Builds a heap from `(parent, child, value)` edges keyed by arbitrary user IDs.
+	/// Children are inserted breadth-first, in the order their edges were given.
+	/// Returns the heap along with a map from each key to its index.
+	pub fn from_edges<K, I>(root_key: K, root: T, edges: I) -> Result<(Self, IndexMap<K>), EdgeError<K>>
+	where
+		K: Key,
+		I: IntoIterator<Item = (K, K, T)>,
+	{
+		let mut edges: Vec<_> = edges.into_iter().map(|(p, c, v)| (p, c, Some(v))).collect();
+		let mut by_child = Map::new();
+		let mut by_parent: Map<&K, Vec<usize>> = Map::new();
+		for (n, (parent, child, _)) in edges.iter().enumerate() {
+			if *child == root_key || by_child.insert(child, n).is_some() {
+				return Err(EdgeError::DuplicateChild(child.clone()));
+			}
+			by_parent.entry(parent).or_default().push(n);
+		}
+
+		let mut order = Vec::with_capacity(edges.len());
+		let mut queue = VecDeque::from([&root_key]);
+		while let Some(parent) = queue.pop_front() {
+			for &n in by_parent.get(parent).into_iter().flatten() {
+				order.push(n);
+				queue.push_back(&edges[n].1);
+			}
+		}
+		if order.len() < edges.len() {
+			let mut reached = vec![false; edges.len()];
+			order.iter().for_each(|&n| reached[n] = true);
+			let first = reached.iter().position(|r| !r).expect("Heap: Internal Error: No unreached edge.");
+			let mut seen = Set::from([&edges[first].1]);
+			let mut key = &edges[first].0;
+			while let Some(&n) = by_child.get(key) {
+				if !seen.insert(key) {
+					return Err(EdgeError::Cycle(key.clone()));
+				}
+				key = &edges[n].0;
+			}
+			return Err(EdgeError::Unreachable(edges[first].1.clone()));
+		}
+
+		let mut heap = Heap::with_capacity(edges.len() + 1, root);
+		let mut indices = IndexMap::new();
+		indices.insert(root_key.clone(), 0);
+		for n in order {
+			let (parent, child, value) = &mut edges[n];
+			let value = value.take().expect("Heap: Internal Error: Edge visited twice.");
+			let index = heap.insert(value, indices[parent]);
+			indices.insert(child.clone(), index);
+		}
+		Ok((heap, indices))
+	}
+
+	/// Builds a heap from parallel arrays, where `parents[i]` is the position of the parent of
+	/// `values[i]` and exactly one position has no parent.
+	/// Positions may appear in any order, as nodes are renumbered breadth-first.
+	/// Returns the heap along with the new index of each position.
+	pub fn try_from_parent_array(
+		values: Vec<T>,
+		parents: &[Option<usize>],
+	) -> Result<(Self, Vec<usize>), ParentArrayError> {
+		if values.len() != parents.len() {
+			return Err(ParentArrayError::LengthMismatch { values: values.len(), parents: parents.len() });
+		}
+		let mut root = None;
+		for (i, parent) in parents.iter().enumerate() {
+			match (parent, root) {
+				(None, None) => root = Some(i),
+				(None, Some(r)) => return Err(ParentArrayError::MultipleRoots(r, i)),
+				(Some(p), _) if *p >= parents.len() => return Err(ParentArrayError::InvalidParent(i)),
+				_ => {}
+			}
+		}
+		let root = root.ok_or(ParentArrayError::NoRoot)?;
+
+		let mut values: Vec<_> = values.into_iter().map(Some).collect();
+		let root_value = values[root].take().expect("Heap: Internal Error: Root taken twice.");
+		let edges = values.into_iter().enumerate().filter_map(|(i, value)|
+			value.map(|v| (parents[i].expect("Heap: Internal Error: Second root."), i, v))
+		);
+		let (heap, indices) = Heap::from_edges(root, root_value, edges).map_err(|e| match e {
+			EdgeError::Cycle(i) => ParentArrayError::Cycle(i),
+			// Positions are unique and every parent is in range, so only cycles can leave nodes unreached
+			EdgeError::DuplicateChild(_) | EdgeError::Unreachable(_) =>
+				unreachable!("Heap: Internal Error: Unexpected edge error."),
+		})?;
+		Ok((heap, (0..parents.len()).map(|i| indices[&i]).collect()))
+	}
+}
+
+impl<T> Heap<T> {
+	/// Builds a heap from `(parent, value)` pairs, inserted in order so that each parent is
+	/// either the root or a node of an earlier pair. The pair at position `i` gets index `i + 1`.
+	pub fn from_pairs<I: IntoIterator<Item = (usize, T)>>(root: T, pairs: I) -> Result<Self, HeapError> {
+		let pairs = pairs.into_iter();
+		let mut heap = Heap::with_capacity(pairs.size_hint().0 + 1, root);
+		heap.try_extend(pairs)?;
+		Ok(heap)
+	}
+	/// Inserts `(parent, value)` pairs in order, as `insert` would, so a pair may name a node
+	/// inserted by an earlier pair as its parent. Every parent is checked before any node is
+	/// inserted, so the heap is left unchanged on error.
+	pub fn try_extend<I: IntoIterator<Item = (usize, T)>>(&mut self, pairs: I) -> Result<(), HeapError> {
+		let pairs: Vec<_> = pairs.into_iter().collect();
+		// The indices `insert` will hand out, in ascending order: the free slots, then new ones
+		let mut assigned = Vec::with_capacity(pairs.len());
+		let (mut free, mut end) = (self.free.first(), self.inner.len());
+		for &(parent, _) in &pairs {
+			if !self.is_valid_idx(parent) && assigned.binary_search(&parent).is_err() {
+				return Err(HeapError::InvalidParent(parent));
+			}
+			let index = match free {
+				Some(i) => {
+					free = self.free.first_from(i + 1);
+					i
+				}
+				None => {
+					end += 1;
+					end - 1
+				}
+			};
+			if !fits_raw_index(index) {
+				return Err(HeapError::CapacityOverflow);
+			}
+			assigned.push(index);
+		}
+		self.inner.reserve(end - self.inner.len());
+		for (parent, value) in pairs {
+			self.insert_unchecked(value, parent);
+		}
+		Ok(())
+	}
+}
+
+/// Panics if a parent is invalid, without inserting any of the nodes
+impl<T> Extend<(usize, T)> for Heap<T> {
+	fn extend<I: IntoIterator<Item = (usize, T)>>(&mut self, pairs: I) {
+		if let Err(e) = self.try_extend(pairs) {
+			panic!("{e}");
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn builder_test() {
+		let mut builder = HeapBuilder::new("root");
+		builder.begin_child("a").child("a1").begin_child("a2");
+		assert_eq!(builder.depth(), 2);
+		builder.child("a2x").end_child().end_child().child("b");
+		let heap = builder.finish();
+		assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec!["root", "a", "a1", "a2", "a2x", "b"]);
+		assert_eq!(heap.parent_of(4), Some(3));
+		assert_eq!(heap.parent_of(5), Some(0));
+	}
+	#[test]
+	#[should_panic]
+	fn builder_end_root_test() {
+		HeapBuilder::new("root").child("a").end_child();
+	}
+
+	#[test]
+	fn from_edges_test() {
+		let edges = vec![("a", "a1", 3), ("root", "a", 1), ("root", "b", 2), ("a1", "a1x", 4)];
+		let (heap, indices) = Heap::from_edges("root", 0, edges).unwrap();
+		assert_eq!(heap.len(), 5);
+		assert_eq!(heap[indices["a1x"]], 4);
+		assert_eq!(heap.parent_of(indices["a1x"]), Some(indices["a1"]));
+		assert_eq!(heap.parent_of(indices["a1"]), Some(indices["a"]));
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![indices["a"], indices["b"]]);
+	}
+
+	#[test]
+	fn from_parent_array_test() {
+		let values = vec!["a1", "root", "a", "b"];
+		let parents = [Some(2), None, Some(1), Some(1)];
+		let (heap, indices) = Heap::try_from_parent_array(values, &parents).unwrap();
+		assert_eq!(indices, vec![3, 0, 1, 2]);
+		assert_eq!(heap[0], "root");
+		assert_eq!(heap.parent_of(indices[0]), Some(indices[2]));
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![1, 2]);
+	}
+
+	#[test]
+	fn from_parent_array_errors_test() {
+		assert_eq!(
+			Heap::try_from_parent_array(vec![1, 2], &[None]).unwrap_err(),
+			ParentArrayError::LengthMismatch { values: 2, parents: 1 },
+		);
+		assert_eq!(Heap::<i32>::try_from_parent_array(vec![], &[]).unwrap_err(), ParentArrayError::NoRoot);
+		assert_eq!(Heap::try_from_parent_array(vec![1, 2], &[None, None]).unwrap_err(), ParentArrayError::MultipleRoots(0, 1));
+		assert_eq!(Heap::try_from_parent_array(vec![1, 2], &[None, Some(2)]).unwrap_err(), ParentArrayError::InvalidParent(1));
+		assert_eq!(Heap::try_from_parent_array(vec![1, 2], &[None, Some(1)]).unwrap_err(), ParentArrayError::Cycle(1));
+		let cycle = [None, Some(2), Some(1)];
+		assert!(matches!(Heap::try_from_parent_array(vec![1, 2, 3], &cycle).unwrap_err(), ParentArrayError::Cycle(_)));
+	}
+
+	#[test]
+	fn from_edges_errors_test() {
+		let duplicate = vec![("root", "a", 1), ("root", "a", 2)];
+		assert_eq!(Heap::from_edges("root", 0, duplicate).unwrap_err(), EdgeError::DuplicateChild("a"));
+		let root_child = vec![("a", "root", 1)];
+		assert_eq!(Heap::from_edges("root", 0, root_child).unwrap_err(), EdgeError::DuplicateChild("root"));
+		let orphan = vec![("root", "a", 1), ("missing", "b", 2), ("b", "c", 3)];
+		assert_eq!(Heap::from_edges("root", 0, orphan).unwrap_err(), EdgeError::Unreachable("b"));
+		let cycle = vec![("root", "a", 1), ("c", "b", 2), ("b", "c", 3)];
+		assert!(matches!(Heap::from_edges("root", 0, cycle).unwrap_err(), EdgeError::Cycle("b" | "c")));
+	}
+
+	#[test]
+	fn extend_test() {
+		let heap = Heap::from_pairs("root", [(0, "a"), (1, "a1"), (0, "b"), (3, "b1")]).unwrap();
+		assert_eq!(heap, crate::tree!("root" => ["a" => ["a1"], "b" => ["b1"]]));
+		assert_eq!(Heap::from_pairs("root", [(0, "a"), (2, "bad")]), Err(HeapError::InvalidParent(2)));
+
+		let mut heap = heap;
+		heap.remove(1);
+		heap.extend([(4, "c"), (1, "c1"), (2, "c2"), (0, "d")]);
+		assert_eq!(heap.children(4).collect::<Vec<_>>(), vec![1]);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![2]);
+		assert_eq!((heap[2], heap[5], heap[6]), ("c1", "c2", "d"));
+		let before = heap.clone();
+		assert_eq!(heap.try_extend([(0, "e"), (7, "f"), (9, "g")]), Err(HeapError::InvalidParent(9)));
+		assert_eq!(heap, before);
+	}
+}
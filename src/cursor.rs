@@ -0,0 +1,179 @@
+use alloc::vec::Vec;
+
+use crate::{Heap, Link, Node};
+
+// Follows the link `step` picks from the node at `index`, if there is one
+fn go<T>(heap: &Heap<T>, index: &mut usize, step: fn(&Node<T>) -> Link) -> bool {
+	match step(&heap.inner[*index]).get() {
+		Some(next) => {
+			*index = next;
+			true
+		}
+		None => false,
+	}
+}
+
+macro_rules! navigation {
+	() => {
+		pub fn index(&self) -> usize {
+			self.index
+		}
+		/// Moves to the parent. Returns `false` and stays put at the root.
+		pub fn go_parent(&mut self) -> bool {
+			go(self.heap, &mut self.index, |node| node.parent)
+		}
+		/// Moves to the first child. Returns `false` and stays put at a leaf.
+		pub fn go_first_child(&mut self) -> bool {
+			go(self.heap, &mut self.index, |node| node.first_child)
+		}
+		/// Moves to the last child. Returns `false` and stays put at a leaf.
+		pub fn go_last_child(&mut self) -> bool {
+			go(self.heap, &mut self.index, |node| node.last_child)
+		}
+		/// Moves to the next sibling. Returns `false` and stays put at the last sibling.
+		pub fn go_next_sibling(&mut self) -> bool {
+			go(self.heap, &mut self.index, |node| node.next_sibling)
+		}
+		/// Moves to the previous sibling. Returns `false` and stays put at the first sibling.
+		pub fn go_prev_sibling(&mut self) -> bool {
+			go(self.heap, &mut self.index, |node| node.prev_sibling)
+		}
+		pub fn go_root(&mut self) {
+			self.index = 0;
+		}
+	};
+}
+
+/// A movable position in a heap, for walking the tree one step at a time.
+/// Created by [`Heap::cursor`].
+pub struct Cursor<'a, T> {
+	heap: &'a Heap<T>,
+	index: usize,
+}
+
+// Derived impls would needlessly require `T: Clone`
+impl<T> Clone for Cursor<'_, T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+impl<T> Copy for Cursor<'_, T> {}
+
+impl<'a, T> Cursor<'a, T> {
+	navigation!();
+	pub fn value(&self) -> &'a T {
+		&self.heap[self.index]
+	}
+}
+
+/// A movable position in a heap that can edit the tree around it.
+/// Created by [`Heap::cursor_mut`].
+pub struct CursorMut<'a, T> {
+	heap: &'a mut Heap<T>,
+	index: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+	navigation!();
+	pub fn value(&self) -> &T {
+		&self.heap[self.index]
+	}
+	pub fn value_mut(&mut self) -> &mut T {
+		&mut self.heap[self.index]
+	}
+	/// Returns the previous value
+	pub fn set_value(&mut self, value: T) -> T {
+		self.heap.replace(self.index, value)
+	}
+	pub fn as_cursor(&self) -> Cursor<'_, T> {
+		Cursor { heap: self.heap, index: self.index }
+	}
+
+	/// Appends `value` as the last child of the current node without moving.
+	/// Returns the index of the new node.
+	pub fn insert_child(&mut self, value: T) -> usize {
+		self.heap.insert(value, self.index)
+	}
+	/// Inserts `value` as the sibling right before the current node without moving.
+	/// Returns the index of the new node.
+	/// Panics at the root
+	pub fn insert_before(&mut self, value: T) -> usize {
+		self.heap.insert_before(value, self.index)
+	}
+	/// Inserts `value` as the sibling right after the current node without moving.
+	/// Returns the index of the new node.
+	/// Panics at the root
+	pub fn insert_after(&mut self, value: T) -> usize {
+		self.heap.insert_after(value, self.index)
+	}
+	/// Removes the current node and its descendants, then moves to its parent.
+	/// Returns the removed values in depth-first pre-order, or `None` at the root, which
+	/// cannot be removed.
+	pub fn remove_here(&mut self) -> Option<Vec<T>> {
+		let parent = self.heap.parent_of(self.index)?;
+		let removed = self.heap.remove(self.index);
+		self.index = parent;
+		Some(removed)
+	}
+}
+
+impl<T> Heap<T> {
+	/// Returns `None` if `index` is invalid
+	pub fn cursor(&self, index: usize) -> Option<Cursor<'_, T>> {
+		self.is_valid_idx(index).then_some(Cursor { heap: self, index })
+	}
+	/// Returns `None` if `index` is invalid
+	pub fn cursor_mut(&mut self, index: usize) -> Option<CursorMut<'_, T>> {
+		self.is_valid_idx(index).then_some(CursorMut { heap: self, index })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use crate::tree;
+
+	#[test]
+	fn cursor_test() {
+		let heap = tree!("root" => ["a" => ["a1", "a2"], "b"]);
+		let mut cursor = heap.cursor(0).unwrap();
+		assert!(!cursor.go_parent());
+		assert!(cursor.go_first_child());
+		assert!(cursor.go_last_child());
+		assert_eq!(*cursor.value(), "a2");
+		assert!(!cursor.go_next_sibling());
+		assert!(cursor.go_prev_sibling());
+		assert_eq!(*cursor.value(), "a1");
+		let saved = cursor;
+		assert!(cursor.go_parent() && cursor.go_next_sibling());
+		assert_eq!((*cursor.value(), *saved.value()), ("b", "a1"));
+		cursor.go_root();
+		assert_eq!(cursor.index(), 0);
+		assert!(heap.cursor(42).is_none());
+	}
+
+	#[test]
+	fn cursor_mut_test() {
+		let mut heap = tree!("root" => ["a" => ["a1"], "b"]);
+		let mut cursor = heap.cursor_mut(0).unwrap();
+		assert_eq!(cursor.remove_here(), None);
+		cursor.go_first_child();
+		let a2 = cursor.insert_child("a2");
+		cursor.insert_after("between");
+		cursor.go_first_child();
+		cursor.insert_before("a0");
+		*cursor.value_mut() = "first";
+		cursor.go_parent();
+		assert_eq!(cursor.set_value("A"), "a");
+		assert_eq!(cursor.as_cursor().index(), 1);
+		cursor.go_next_sibling();
+		assert_eq!(*cursor.value(), "between");
+		cursor.go_prev_sibling();
+		cursor.go_last_child();
+		assert_eq!(cursor.index(), a2);
+		assert_eq!(cursor.remove_here(), Some(vec!["a2"]));
+		assert_eq!(*cursor.value(), "A");
+		assert_eq!(heap, tree!("root" => ["A" => ["a0", "first"], "between", "b"]));
+	}
+}
@@ -0,0 +1,127 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::{Heap, Key, Map};
+
+/// A node that an edit attaches to
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EditNode {
+	/// A node already in the heap being edited, by its index there
+	Old(usize),
+	/// A node added by an earlier `Insert` of the same script, by its `from` index
+	New(usize),
+}
+
+/// One step of an edit script produced by [`Heap::diff`]. Steps are meant to be applied in
+/// order, and indices refer to the heap being edited unless stated otherwise.
+#[derive(Clone, Debug, PartialEq)]
+pub enum TreeEdit<T> {
+	/// Appends a new node as the last child of `parent`. `from` is the node's index in the
+	/// heap the script was computed against.
+	Insert { from: usize, parent: EditNode, value: T },
+	/// Makes `index` the last child of `parent`, carrying its descendants along
+	Move { index: usize, parent: EditNode },
+	/// Removes `index` along with all of its remaining descendants
+	Remove { index: usize },
+	/// Replaces the value at `index`
+	Update { index: usize, value: T },
+}
+
+impl<T: Clone + PartialEq> Heap<T> {
+	/// Computes an edit script that turns `self` into a heap shaped like `other`.
+	/// Nodes are matched by the keys `key` gives their values: each node of `other` is paired
+	/// with the first unpaired node of `self`, in depth-first pre-order, that has the same key.
+	/// The roots are always paired. Paired nodes are kept and moved where needed, their
+	/// values are updated when they differ, and every other node is inserted or removed.
+	/// Moves come before removals, so no paired node is removed along with an old ancestor.
+	pub fn diff<K: Key, F: Fn(&T) -> K>(&self, other: &Heap<T>, key: F) -> Vec<TreeEdit<T>> {
+		let mut candidates: Map<K, VecDeque<usize>> = Map::new();
+		for (i, value) in self.iter_dfs(0).skip(1) {
+			candidates.entry(key(value)).or_default().push_back(i);
+		}
+		let mut matched = vec![None; other.inner.len()];
+		let mut is_matched = vec![false; self.inner.len()];
+		(matched[0], is_matched[0]) = (Some(0), true);
+		for (j, value) in other.iter_dfs(0).skip(1) {
+			if let Some(i) = candidates.get_mut(&key(value)).and_then(VecDeque::pop_front) {
+				(matched[j], is_matched[i]) = (Some(i), true);
+			}
+		}
+
+		// Every parent is placed before its children are, so a move can never form a cycle
+		let mut edits = Vec::new();
+		for (p, _) in other.iter_dfs(0) {
+			let parent = match matched[p] {
+				Some(q) => EditNode::Old(q),
+				None => EditNode::New(p),
+			};
+			let desired: Vec<_> = other.children(p).collect();
+			// Leading children that are already in order under the paired parent can stay
+			let mut kept = 0;
+			if let Some(q) = matched[p] {
+				let mut current = self.children(q);
+				while kept < desired.len() {
+					match matched[desired[kept]] {
+						Some(i) if current.any(|c| c == i) => kept += 1,
+						_ => break,
+					}
+				}
+			}
+			for &d in &desired[kept..] {
+				edits.push(match matched[d] {
+					Some(index) => TreeEdit::Move { index, parent },
+					None => TreeEdit::Insert { from: d, parent, value: other[d].clone() },
+				});
+			}
+		}
+		for (i, _) in self.iter_dfs(0).skip(1) {
+			let parent = self.inner[i].parent().expect("Heap: Internal Error: Orphaned node.");
+			if !is_matched[i] && is_matched[parent] {
+				edits.push(TreeEdit::Remove { index: i });
+			}
+		}
+		for (j, value) in other.iter_dfs(0) {
+			if let Some(index) = matched[j] {
+				if self[index] != *value {
+					edits.push(TreeEdit::Update { index, value: value.clone() });
+				}
+			}
+		}
+		edits
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::{EditNode, TreeEdit};
+	use crate::tree;
+
+	#[test]
+	fn diff_test() {
+		let old = tree!((0, "root") => [(1, "a") => [(3, "a1")], (2, "b") => [(4, "b1")]]);
+		let new = tree!((0, "root") => [(2, "b"), (1, "A") => [(4, "b1"), (5, "c")]]);
+		let edits = old.diff(&new, |&(id, _)| id);
+		assert_eq!(edits, vec![
+			TreeEdit::Move { index: 1, parent: EditNode::Old(0) },
+			TreeEdit::Move { index: 4, parent: EditNode::Old(1) },
+			TreeEdit::Insert { from: 4, parent: EditNode::Old(1), value: (5, "c") },
+			TreeEdit::Remove { index: 2 },
+			TreeEdit::Update { index: 1, value: (1, "A") },
+		]);
+		assert_eq!(old.diff(&old, |&(id, _)| id), vec![]);
+	}
+
+	#[test]
+	fn diff_new_parent_test() {
+		let old = tree!("root" => ["a", "b"]);
+		let new = tree!("root" => ["a", "x" => ["b", "y"]]);
+		assert_eq!(old.diff(&new, |&v| v), vec![
+			TreeEdit::Insert { from: 2, parent: EditNode::Old(0), value: "x" },
+			TreeEdit::Move { index: 2, parent: EditNode::New(2) },
+			TreeEdit::Insert { from: 4, parent: EditNode::New(2), value: "y" },
+		]);
+	}
+}
@@ -0,0 +1,63 @@
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display, Write};
+
+use crate::Heap;
+
+impl<T> Heap<T> {
+	/// Renders the heap as a Graphviz DOT digraph, labelling each node with what `label`
+	/// returns for its value and drawing an edge from every child to its parent
+	pub fn to_dot<F: FnMut(&T) -> L, L: Display>(&self, label: F) -> String {
+		let mut out = String::new();
+		self.write_dot(&mut out, label).expect("Heap: Internal Error: Writing to a String failed.");
+		out
+	}
+	pub fn write_dot<W: Write, F: FnMut(&T) -> L, L: Display>(&self, out: &mut W, mut label: F) -> fmt::Result {
+		writeln!(out, "digraph heap {{")?;
+		for (i, value) in self.iter_dfs(0) {
+			writeln!(out, "\tn{i} [label=\"{}\"];", escape(&label(value).to_string()))?;
+		}
+		for (i, _) in self.iter_dfs(0) {
+			if let Some(parent) = self.inner[i].parent() {
+				writeln!(out, "\tn{i} -> n{parent};")?;
+			}
+		}
+		writeln!(out, "}}")
+	}
+}
+
+fn escape(label: &str) -> String {
+	let mut escaped = String::with_capacity(label.len());
+	for c in label.chars() {
+		match c {
+			'"' | '\\' => {
+				escaped.push('\\');
+				escaped.push(c);
+			}
+			'\n' => escaped.push_str("\\n"),
+			_ => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Heap;
+
+	#[test]
+	fn to_dot_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("say \"hi\"", 0);
+		heap.insert("b", a);
+		assert_eq!(heap.to_dot(|&name| name), concat!(
+			"digraph heap {\n",
+			"\tn0 [label=\"root\"];\n",
+			"\tn1 [label=\"say \\\"hi\\\"\"];\n",
+			"\tn2 [label=\"b\"];\n",
+			"\tn1 -> n0;\n",
+			"\tn2 -> n1;\n",
+			"}\n",
+		));
+		assert!(heap.to_dot(|name| name.len()).contains("\tn2 [label=\"1\"];\n"));
+	}
+}
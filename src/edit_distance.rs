@@ -0,0 +1,213 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::Heap;
+
+/// The result of [`Heap::edit_distance`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EditDistance {
+	/// The total cost of the cheapest sequence of edits
+	pub distance: usize,
+	/// How the nodes of both heaps correspond under those edits, as pairs of indices.
+	/// `(Some(a), Some(b))` keeps `a` as `b`, relabelling it if needed, `(Some(a), None)`
+	/// deletes `a` and `(None, Some(b))` inserts `b`.
+	/// Every node of either heap appears exactly once, in no particular order.
+	pub alignment: Vec<(Option<usize>, Option<usize>)>,
+}
+
+// A heap numbered in post-order from 1, as Zhang and Shasha's algorithm expects
+struct PostOrder<'a, T> {
+	heap: &'a Heap<T>,
+	// Heap index of each post-order number. The unused 0 keeps the arithmetic simple.
+	index: Vec<usize>,
+	// Post-order number of the leftmost leaf below each node
+	leftmost: Vec<usize>,
+	// The highest node of each leftmost leaf, in increasing order
+	keyroots: Vec<usize>,
+}
+
+impl<'a, T> PostOrder<'a, T> {
+	fn new(heap: &'a Heap<T>) -> Self {
+		let mut number = vec![0; heap.inner.len()];
+		let (mut index, mut leftmost) = (vec![0], vec![0]);
+		for (i, _) in heap.iter_post_order(0) {
+			let n = index.len();
+			number[i] = n;
+			index.push(i);
+			leftmost.push(match heap.inner[i].first_child.get() {
+				Some(child) => leftmost[number[child]],
+				None => n,
+			});
+		}
+		let mut seen = vec![false; index.len()];
+		let mut keyroots = Vec::new();
+		for n in (1..index.len()).rev() {
+			if !core::mem::replace(&mut seen[leftmost[n]], true) {
+				keyroots.push(n);
+			}
+		}
+		keyroots.reverse();
+		Self { heap, index, leftmost, keyroots }
+	}
+	fn value(&self, n: usize) -> &'a T {
+		&self.heap[self.index[n]]
+	}
+}
+
+struct Solver<'a, T, U, F> {
+	a: PostOrder<'a, T>,
+	b: PostOrder<'a, U>,
+	cost: F,
+	// Distance between every pair of subtrees, filled in keyroot by keyroot
+	trees: Vec<Vec<usize>>,
+}
+
+impl<T, U, F: Fn(Option<&T>, Option<&U>) -> usize> Solver<'_, T, U, F> {
+	fn delete(&self, x: usize) -> usize {
+		(self.cost)(Some(self.a.value(x)), None)
+	}
+	fn insert(&self, y: usize) -> usize {
+		(self.cost)(None, Some(self.b.value(y)))
+	}
+	fn relabel(&self, x: usize, y: usize) -> usize {
+		(self.cost)(Some(self.a.value(x)), Some(self.b.value(y)))
+	}
+
+	// Distances between the forests leading up to `i` and `j`, offset so that [0][0] holds
+	// the empty forests
+	fn forests(&mut self, i: usize, j: usize) -> Vec<Vec<usize>> {
+		let (li, lj) = (self.a.leftmost[i], self.b.leftmost[j]);
+		let mut forests = vec![vec![0; j - lj + 2]; i - li + 2];
+		for x in li..=i {
+			forests[x - li + 1][0] = forests[x - li][0] + self.delete(x);
+		}
+		for y in lj..=j {
+			forests[0][y - lj + 1] = forests[0][y - lj] + self.insert(y);
+		}
+		for x in li..=i {
+			for y in lj..=j {
+				let (fx, fy) = (x - li + 1, y - lj + 1);
+				let edit = (forests[fx - 1][fy] + self.delete(x)).min(forests[fx][fy - 1] + self.insert(y));
+				let (lx, ly) = (self.a.leftmost[x], self.b.leftmost[y]);
+				forests[fx][fy] = if lx == li && ly == lj {
+					let distance = edit.min(forests[fx - 1][fy - 1] + self.relabel(x, y));
+					self.trees[x][y] = distance;
+					distance
+				} else {
+					edit.min(forests[lx - li][ly - lj] + self.trees[x][y])
+				};
+			}
+		}
+		forests
+	}
+
+	fn solve(mut self) -> EditDistance {
+		let (a, b) = (core::mem::take(&mut self.a.keyroots), core::mem::take(&mut self.b.keyroots));
+		for &i in &a {
+			for &j in &b {
+				self.forests(i, j);
+			}
+		}
+		let (n, m) = (self.a.index.len() - 1, self.b.index.len() - 1);
+		let distance = self.trees[n][m];
+
+		// Walk back through the tables, recomputing the forests of each pair of subtrees
+		// that the cheapest edits keep together
+		let mut alignment = Vec::with_capacity(n + m);
+		let mut pending = vec![(n, m)];
+		while let Some((i, j)) = pending.pop() {
+			let forests = self.forests(i, j);
+			let (li, lj) = (self.a.leftmost[i], self.b.leftmost[j]);
+			let (mut x, mut y) = (i, j);
+			while x >= li || y >= lj {
+				let (fx, fy) = (x + 1 - li, y + 1 - lj);
+				if x >= li && (y < lj || forests[fx][fy] == forests[fx - 1][fy] + self.delete(x)) {
+					alignment.push((Some(self.a.index[x]), None));
+					x -= 1;
+				} else if y >= lj && (x < li || forests[fx][fy] == forests[fx][fy - 1] + self.insert(y)) {
+					alignment.push((None, Some(self.b.index[y])));
+					y -= 1;
+				} else if self.a.leftmost[x] == li && self.b.leftmost[y] == lj {
+					alignment.push((Some(self.a.index[x]), Some(self.b.index[y])));
+					x -= 1;
+					y -= 1;
+				} else {
+					pending.push((x, y));
+					x = self.a.leftmost[x] - 1;
+					y = self.b.leftmost[y] - 1;
+				}
+			}
+		}
+		EditDistance { distance, alignment }
+	}
+}
+
+impl<T> Heap<T> {
+	/// Computes the cheapest way to turn `self` into `other` by deleting, inserting and
+	/// relabelling nodes, using Zhang and Shasha's algorithm for ordered trees.
+	/// Deleting a node moves its children up into its place among its siblings, and
+	/// inserting one adopts a consecutive run of siblings.
+	/// `cost(Some(a), None)` is the cost of deleting `a`, `cost(None, Some(b))` that of
+	/// inserting `b` and `cost(Some(a), Some(b))` that of relabelling `a` as `b`.
+	/// Takes O(n² m²) time in the worst case and O(n m) memory.
+	pub fn edit_distance_by<U, F>(&self, other: &Heap<U>, cost: F) -> EditDistance
+	where
+		F: Fn(Option<&T>, Option<&U>) -> usize,
+	{
+		let (a, b) = (PostOrder::new(self), PostOrder::new(other));
+		let trees = vec![vec![0; b.index.len()]; a.index.len()];
+		Solver { a, b, cost, trees }.solve()
+	}
+}
+
+impl<T: PartialEq> Heap<T> {
+	/// `edit_distance_by` where every deletion and insertion costs 1, and relabelling costs 1
+	/// if the values differ and nothing otherwise
+	pub fn edit_distance(&self, other: &Heap<T>) -> EditDistance {
+		self.edit_distance_by(other, |a, b| usize::from(a != b))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use crate::tree;
+
+	#[test]
+	fn edit_distance_test() {
+		let a = tree!("f" => ["d" => ["a", "c" => ["b"]], "e"]);
+		let b = tree!("f" => ["c" => ["d" => ["a", "b"]], "e"]);
+		let result = a.edit_distance(&b);
+		assert_eq!(result.distance, 2);
+		assert_eq!(result.alignment.len(), 7);
+		let mut kept: Vec<_> = result.alignment.iter()
+			.filter_map(|&pair| match pair {
+				(Some(x), Some(y)) => Some((a[x], b[y])),
+				_ => None,
+			})
+			.collect();
+		kept.sort();
+		assert_eq!(kept, [("a", "a"), ("b", "b"), ("d", "d"), ("e", "e"), ("f", "f")]);
+		assert_eq!(a.edit_distance(&a).distance, 0);
+	}
+
+	#[test]
+	fn edit_distance_costs_test() {
+		let a = tree!("root" => ["x", "y"]);
+		let b = tree!("root" => ["z" => ["x", "y"]]);
+		assert_eq!(a.edit_distance(&b).distance, 1);
+		// Relabelling costs more than deleting and inserting
+		let result = a.edit_distance_by(&b, |x, y| match (x, y) {
+			(Some(x), Some(y)) if x == y => 0,
+			(Some(_), Some(_)) => 5,
+			_ => 2,
+		});
+		assert_eq!(result.distance, 2);
+		assert!(result.alignment.contains(&(None, Some(1))));
+		let single = tree!("root");
+		let result = single.edit_distance(&a);
+		assert_eq!(result.distance, 2);
+		assert_eq!(result.alignment.len(), 3);
+	}
+}
@@ -0,0 +1,67 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use ego_tree::{NodeRef, Tree};
+
+use crate::Heap;
+
+/// Appends the nodes in depth-first pre-order, keeping the order of every node's children
+impl<T: Clone> From<&Heap<T>> for Tree<T> {
+	fn from(heap: &Heap<T>) -> Self {
+		let mut tree = Tree::with_capacity(heap[0].clone(), heap.len());
+		let mut ids = vec![None; heap.inner.len()];
+		ids[0] = Some(tree.root().id());
+		for (i, value) in heap.iter_dfs(0).skip(1) {
+			let parent = heap.inner[i].parent().and_then(|p| ids[p])
+				.expect("Heap: Internal Error: Child visited before parent.");
+			let mut parent = tree.get_mut(parent).expect("Heap: Internal Error: Missing ego-tree node.");
+			ids[i] = Some(parent.append(value.clone()).id());
+		}
+		tree
+	}
+}
+
+/// Copies the subtree below the node, numbering its nodes in depth-first pre-order and keeping
+/// the order of every node's children
+impl<T: Clone> From<NodeRef<'_, T>> for Heap<T> {
+	fn from(root: NodeRef<'_, T>) -> Self {
+		let mut heap = Heap::new(root.value().clone());
+		let mut stack: Vec<_> = root.children().rev().map(|c| (c, 0)).collect();
+		while let Some((node, parent)) = stack.pop() {
+			let index = heap.insert(node.value().clone(), parent);
+			stack.extend(node.children().rev().map(|c| (c, index)));
+		}
+		heap
+	}
+}
+
+impl<T: Clone> From<&Tree<T>> for Heap<T> {
+	fn from(tree: &Tree<T>) -> Self {
+		Self::from(tree.root())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use ego_tree::{tree, Tree};
+
+	use crate::Heap;
+
+	#[test]
+	fn ego_tree_round_trip_test() {
+		let mut heap = crate::tree!("html" => ["head" => ["title"], "body" => ["h1", "p"]]);
+		heap.move_child(3, 0);
+		let tree = Tree::from(&heap);
+		let order: Vec<_> = tree.root().descendants().map(|node| *node.value()).collect();
+		assert_eq!(order, ["html", "body", "h1", "p", "head", "title"]);
+		let back = Heap::from(&tree);
+		assert_eq!(back.iter_dfs(0).map(|(_, &v)| v).collect::<Vec<_>>(), order);
+
+		let scraped = tree!("html" => { "body" => { "div" => { "a", "b" }, "footer" } });
+		let div = scraped.root().first_child().and_then(|body| body.first_child()).unwrap();
+		assert_eq!(Heap::from(div), crate::tree!("div" => ["a", "b"]));
+		assert_eq!(Heap::from(&scraped).len(), 6);
+	}
+}
@@ -0,0 +1,96 @@
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+const BITS: usize = u64::BITS as usize;
+
+/// The indices of free slots, stored as a two-level bitmap so that the lowest free index
+/// can be found without scanning every slot
+#[derive(Clone, Debug, Default)]
+pub(crate) struct FreeList {
+	// Bit `i % 64` of word `i / 64` is set when slot `i` is free
+	words: Vec<u64>,
+	// Bit `w % 64` of summary word `w / 64` is set when word `w` has any bit set
+	summary: Vec<u64>,
+}
+
+impl FreeList {
+	pub(crate) fn new() -> Self {
+		Self::default()
+	}
+	pub(crate) fn insert(&mut self, index: usize) {
+		let (w, bit) = (index / BITS, 1 << (index % BITS));
+		if w >= self.words.len() {
+			self.words.resize(w + 1, 0);
+			self.summary.resize(w / BITS + 1, 0);
+		}
+		self.words[w] |= bit;
+		self.summary[w / BITS] |= 1 << (w % BITS);
+	}
+	pub(crate) fn remove(&mut self, index: usize) {
+		let w = index / BITS;
+		let Some(word) = self.words.get_mut(w) else {
+			return;
+		};
+		*word &= !(1 << (index % BITS));
+		if *word == 0 {
+			self.summary[w / BITS] &= !(1 << (w % BITS));
+		}
+	}
+	pub(crate) fn clear(&mut self) {
+		self.words.clear();
+		self.summary.clear();
+	}
+	pub(crate) fn first(&self) -> Option<usize> {
+		self.first_from(0)
+	}
+	// The lowest free index that is at least `start`
+	pub(crate) fn first_from(&self, start: usize) -> Option<usize> {
+		let w = start / BITS;
+		let word = *self.words.get(w)? & (!0 << (start % BITS));
+		if word != 0 {
+			return Some(w * BITS + word.trailing_zeros() as usize);
+		}
+		// Skip straight to the next word with a free slot using the summary
+		let w = w + 1;
+		let mut s = w / BITS;
+		let mut summary = *self.summary.get(s)? & (!0 << (w % BITS));
+		loop {
+			if summary != 0 {
+				let w = s * BITS + summary.trailing_zeros() as usize;
+				return Some(w * BITS + self.words[w].trailing_zeros() as usize);
+			}
+			s += 1;
+			summary = *self.summary.get(s)?;
+		}
+	}
+	pub(crate) fn memory_usage(&self) -> usize {
+		(self.words.capacity() + self.summary.capacity()) * size_of::<u64>()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::FreeList;
+
+	#[test]
+	fn first_from_test() {
+		let mut free = FreeList::new();
+		assert_eq!(free.first(), None);
+		for i in [3, 64, 70, 5000] {
+			free.insert(i);
+		}
+		assert_eq!(free.first(), Some(3));
+		assert_eq!(free.first_from(4), Some(64));
+		assert_eq!(free.first_from(65), Some(70));
+		assert_eq!(free.first_from(71), Some(5000));
+		assert_eq!(free.first_from(5001), None);
+		free.remove(3);
+		free.remove(64);
+		free.remove(64);
+		assert_eq!(free.first(), Some(70));
+		free.remove(70);
+		assert_eq!(free.first(), Some(5000));
+		free.clear();
+		assert_eq!(free.first(), None);
+	}
+}
@@ -0,0 +1,119 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Write};
+
+use crate::Heap;
+
+impl<T> Heap<T> {
+	/// Renders the heap as a GraphML document with an edge from every parent to each of its
+	/// children. `attributes` gives the `(name, value)` data of each node, and every name it
+	/// ever returns is declared as a string attribute, in order of first appearance.
+	pub fn to_graphml<F, I, K, V>(&self, attributes: F) -> String
+	where
+		F: FnMut(&T) -> I,
+		I: IntoIterator<Item = (K, V)>,
+		K: Into<String>,
+		V: Display,
+	{
+		let mut out = String::new();
+		self.write_graphml(&mut out, attributes).expect("Heap: Internal Error: Writing to a String failed.");
+		out
+	}
+	pub fn write_graphml<W, F, I, K, V>(&self, out: &mut W, mut attributes: F) -> fmt::Result
+	where
+		W: Write,
+		F: FnMut(&T) -> I,
+		I: IntoIterator<Item = (K, V)>,
+		K: Into<String>,
+		V: Display,
+	{
+		// Names are needed up front for the key declarations
+		let mut names: Vec<String> = Vec::new();
+		let mut nodes = Vec::with_capacity(self.len());
+		for (i, value) in self.iter_dfs(0) {
+			let data: Vec<_> = attributes(value).into_iter().map(|(name, value)| {
+				let name = name.into();
+				let key = names.iter().position(|n| *n == name).unwrap_or_else(|| {
+					names.push(name);
+					names.len() - 1
+				});
+				(key, value)
+			}).collect();
+			nodes.push((i, data));
+		}
+
+		writeln!(out, "<?xml version=\"1.0\" encoding=\"UTF-8\"?>")?;
+		writeln!(out, "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">")?;
+		for (key, name) in names.iter().enumerate() {
+			write!(out, "\t<key id=\"d{key}\" for=\"node\" attr.name=\"")?;
+			write_escaped(out, name)?;
+			writeln!(out, "\" attr.type=\"string\"/>")?;
+		}
+		writeln!(out, "\t<graph id=\"heap\" edgedefault=\"directed\">")?;
+		for (i, data) in nodes {
+			write!(out, "\t\t<node id=\"n{i}\">")?;
+			for (key, value) in data {
+				write!(out, "<data key=\"d{key}\">")?;
+				write_escaped(out, &value.to_string())?;
+				write!(out, "</data>")?;
+			}
+			writeln!(out, "</node>")?;
+		}
+		for (parent, child) in self.edges() {
+			writeln!(out, "\t\t<edge source=\"n{parent}\" target=\"n{child}\"/>")?;
+		}
+		writeln!(out, "\t</graph>")?;
+		writeln!(out, "</graphml>")
+	}
+}
+
+fn write_escaped<W: Write>(out: &mut W, text: &str) -> fmt::Result {
+	for c in text.chars() {
+		match c {
+			'&' => out.write_str("&amp;")?,
+			'<' => out.write_str("&lt;")?,
+			'>' => out.write_str("&gt;")?,
+			'"' => out.write_str("&quot;")?,
+			'\'' => out.write_str("&apos;")?,
+			_ => out.write_char(c)?,
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::ToString;
+	use alloc::vec;
+	use alloc::vec::Vec;
+
+	use crate::tree;
+
+	#[test]
+	fn to_graphml_test() {
+		let heap = tree!(("root", 0) => [("a & b", 3), ("<c>", 1)]);
+		let graphml = heap.to_graphml(|&(name, size)| {
+			let mut data = vec![("label", name.to_owned())];
+			if size > 0 {
+				data.push(("size", size.to_string()));
+			}
+			data
+		});
+		assert_eq!(graphml, concat!(
+			"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+			"<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+			"\t<key id=\"d0\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+			"\t<key id=\"d1\" for=\"node\" attr.name=\"size\" attr.type=\"string\"/>\n",
+			"\t<graph id=\"heap\" edgedefault=\"directed\">\n",
+			"\t\t<node id=\"n0\"><data key=\"d0\">root</data></node>\n",
+			"\t\t<node id=\"n1\"><data key=\"d0\">a &amp; b</data><data key=\"d1\">3</data></node>\n",
+			"\t\t<node id=\"n2\"><data key=\"d0\">&lt;c&gt;</data><data key=\"d1\">1</data></node>\n",
+			"\t\t<edge source=\"n0\" target=\"n1\"/>\n",
+			"\t\t<edge source=\"n0\" target=\"n2\"/>\n",
+			"\t</graph>\n",
+			"</graphml>\n",
+		));
+		let empty = heap.to_graphml(|_| Vec::<(&str, &str)>::new());
+		assert!(empty.contains("<node id=\"n2\"></node>"));
+	}
+}
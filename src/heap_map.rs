@@ -0,0 +1,125 @@
+use alloc::vec::Vec;
+
+use crate::{Heap, NodeId};
+
+/// Extra per-node data kept outside a heap, keyed by [`NodeId`].
+/// An entry only answers to the exact `NodeId` it was inserted with, so once a node is removed
+/// and its slot is reused, lookups with either the old or the new `NodeId` never see data
+/// belonging to the other node. Entries of removed nodes can be dropped with `retain_live`.
+#[derive(Clone, Debug)]
+pub struct HeapMap<V> {
+	// Indexed by slot, holding the generation each entry was inserted for
+	slots: Vec<Option<(u32, V)>>,
+	len: usize,
+}
+
+impl<V> Default for HeapMap<V> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<V> HeapMap<V> {
+	pub fn new() -> Self {
+		Self { slots: Vec::new(), len: 0 }
+	}
+	pub fn len(&self) -> usize {
+		self.len
+	}
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+	/// Returns the previous value for `id`. An entry left behind by an earlier node in the
+	/// same slot is replaced without being returned.
+	pub fn insert(&mut self, id: NodeId, value: V) -> Option<V> {
+		if id.index >= self.slots.len() {
+			self.slots.resize_with(id.index + 1, || None);
+		}
+		match self.slots[id.index].replace((id.generation, value)) {
+			Some((generation, old)) if generation == id.generation => Some(old),
+			Some(_) => None,
+			None => {
+				self.len += 1;
+				None
+			}
+		}
+	}
+	pub fn get(&self, id: NodeId) -> Option<&V> {
+		match self.slots.get(id.index)? {
+			Some((generation, value)) if *generation == id.generation => Some(value),
+			_ => None,
+		}
+	}
+	pub fn get_mut(&mut self, id: NodeId) -> Option<&mut V> {
+		match self.slots.get_mut(id.index)? {
+			Some((generation, value)) if *generation == id.generation => Some(value),
+			_ => None,
+		}
+	}
+	pub fn contains_key(&self, id: NodeId) -> bool {
+		self.get(id).is_some()
+	}
+	pub fn remove(&mut self, id: NodeId) -> Option<V> {
+		let slot = self.slots.get_mut(id.index)?;
+		match slot {
+			Some((generation, _)) if *generation == id.generation => {
+				self.len -= 1;
+				slot.take().map(|(_, value)| value)
+			}
+			_ => None,
+		}
+	}
+	/// Drops every entry whose node is no longer in `heap`
+	pub fn retain_live<T>(&mut self, heap: &Heap<T>) {
+		for (index, slot) in self.slots.iter_mut().enumerate() {
+			if let Some((generation, _)) = slot {
+				if !heap.contains_id(NodeId { index, generation: *generation }) {
+					*slot = None;
+					self.len -= 1;
+				}
+			}
+		}
+	}
+	/// Yields every entry in index order
+	pub fn iter(&self) -> impl Iterator<Item = (NodeId, &V)> {
+		self.slots.iter().enumerate().filter_map(|(index, slot)| {
+			slot.as_ref().map(|(generation, value)| (NodeId { index, generation: *generation }, value))
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use crate::{Heap, HeapMap};
+
+	#[test]
+	fn heap_map_test() {
+		let mut heap = Heap::new("root");
+		let root = heap.id_of(0).unwrap();
+		let a = heap.insert_id("a", root);
+		let b = heap.insert_id("b", root);
+		let mut map = HeapMap::new();
+		assert_eq!(map.insert(a, 1), None);
+		assert_eq!(map.insert(b, 2), None);
+		assert_eq!(map.insert(a, 10), Some(1));
+		*map.get_mut(b).unwrap() += 1;
+		assert_eq!((map.get(a), map.get(b), map.len()), (Some(&10), Some(&3), 2));
+
+		heap.remove(a.index());
+		let reused = heap.insert_id("reused", root);
+		assert_eq!(reused.index(), a.index());
+		assert_eq!(map.get(reused), None);
+		assert_eq!(map.insert(reused, 20), None);
+		assert_eq!(map.get(a), None);
+		assert_eq!(map.len(), 2);
+
+		heap.remove(b.index());
+		map.retain_live(&heap);
+		assert!(!map.contains_key(b));
+		assert_eq!(map.iter().collect::<Vec<_>>(), [(reused, &20)]);
+		assert_eq!(map.remove(reused), Some(20));
+		assert!(map.is_empty());
+	}
+}
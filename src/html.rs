@@ -0,0 +1,113 @@
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display, Write};
+use core::ops::ControlFlow;
+
+use crate::{Heap, Visitor, WalkControl};
+
+struct Writer<'a, T, W, F> {
+	heap: &'a Heap<T>,
+	out: &'a mut W,
+	label: F,
+}
+
+impl<T, W: Write, F: FnMut(&T) -> L, L: Display> Visitor<T> for Writer<'_, T, W, F> {
+	type Break = fmt::Error;
+	fn enter(&mut self, index: usize, value: &T) -> WalkControl<fmt::Error> {
+		match self.open(index, value) {
+			Ok(()) => WalkControl::Continue,
+			Err(e) => WalkControl::Break(e),
+		}
+	}
+	fn leave(&mut self, index: usize, _: &T) -> ControlFlow<fmt::Error> {
+		match self.close(index) {
+			Ok(()) => ControlFlow::Continue(()),
+			Err(e) => ControlFlow::Break(e),
+		}
+	}
+}
+
+impl<T, W: Write, F: FnMut(&T) -> L, L: Display> Writer<'_, T, W, F> {
+	fn indent(&mut self, level: usize) -> fmt::Result {
+		(0..level).try_for_each(|_| self.out.write_char('\t'))
+	}
+	// Each node sits one level deeper than the list holding it, and its own list one deeper still
+	fn open(&mut self, index: usize, value: &T) -> fmt::Result {
+		let depth = self.heap.depth_of(index);
+		self.indent(2 * depth + 1)?;
+		write!(self.out, "<li>")?;
+		write_escaped(self.out, &(self.label)(value).to_string())?;
+		if self.heap.is_leaf(index) {
+			return writeln!(self.out, "</li>");
+		}
+		writeln!(self.out)?;
+		self.indent(2 * depth + 2)?;
+		writeln!(self.out, "<ul>")
+	}
+	fn close(&mut self, index: usize) -> fmt::Result {
+		if self.heap.is_leaf(index) {
+			return Ok(());
+		}
+		let depth = self.heap.depth_of(index);
+		self.indent(2 * depth + 2)?;
+		writeln!(self.out, "</ul>")?;
+		self.indent(2 * depth + 1)?;
+		writeln!(self.out, "</li>")
+	}
+}
+
+impl<T> Heap<T> {
+	/// Renders the heap as nested HTML `<ul>` and `<li>` elements, with the root as the only
+	/// item of the outermost list. Each item holds what `label` returns for the node's value,
+	/// with every character that is special in HTML escaped, so labels can never inject markup.
+	pub fn to_html<F: FnMut(&T) -> L, L: Display>(&self, label: F) -> String {
+		let mut out = String::new();
+		self.write_html(&mut out, label).expect("Heap: Internal Error: Writing to a String failed.");
+		out
+	}
+	pub fn write_html<W: Write, F: FnMut(&T) -> L, L: Display>(&self, out: &mut W, label: F) -> fmt::Result {
+		writeln!(out, "<ul>")?;
+		if let Some(e) = self.walk(0, &mut Writer { heap: self, out: &mut *out, label }) {
+			return Err(e);
+		}
+		writeln!(out, "</ul>")
+	}
+}
+
+fn write_escaped<W: Write>(out: &mut W, text: &str) -> fmt::Result {
+	for c in text.chars() {
+		match c {
+			'&' => out.write_str("&amp;")?,
+			'<' => out.write_str("&lt;")?,
+			'>' => out.write_str("&gt;")?,
+			'"' => out.write_str("&quot;")?,
+			'\'' => out.write_str("&#39;")?,
+			_ => out.write_char(c)?,
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{tree, Heap};
+
+	#[test]
+	fn to_html_test() {
+		let heap = tree!("CEO" => ["<script>" => ["R&D"], "Sales"]);
+		assert_eq!(heap.to_html(|&name| name), concat!(
+			"<ul>\n",
+			"\t<li>CEO\n",
+			"\t\t<ul>\n",
+			"\t\t\t<li>&lt;script&gt;\n",
+			"\t\t\t\t<ul>\n",
+			"\t\t\t\t\t<li>R&amp;D</li>\n",
+			"\t\t\t\t</ul>\n",
+			"\t\t\t</li>\n",
+			"\t\t\t<li>Sales</li>\n",
+			"\t\t</ul>\n",
+			"\t</li>\n",
+			"</ul>\n",
+		));
+		assert_eq!(Heap::new(7).to_html(|n| n * 6), "<ul>\n\t<li>42</li>\n</ul>\n");
+	}
+}
@@ -0,0 +1,145 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::Heap;
+
+/// How [`Heap::from_indented`] recognises one level of indentation
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Indent {
+	/// Uses whatever leads the first indented line, which must be all spaces or all tabs
+	Detect,
+	/// Every level is this many spaces
+	Spaces(usize),
+	/// Every level is one tab
+	Tabs,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IndentError {
+	/// The input has no lines that are not blank
+	Empty,
+	/// The indentation of line `line` mixes tabs and spaces, or uses the wrong one
+	Mixed { line: usize },
+	/// The indentation of line `line` is not a whole number of levels
+	Uneven { line: usize },
+	/// Line `line` is indented more than one level deeper than the line before it
+	TooDeep { line: usize },
+	/// Line `line` is not indented, but the first line already is the root
+	SecondRoot { line: usize },
+}
+
+impl fmt::Display for IndentError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Empty => write!(f, "Heap: Error: The outline is empty"),
+			Self::Mixed { line } => write!(f, "Heap: Error: Line {line} of the outline mixes tabs and spaces"),
+			Self::Uneven { line } => write!(f, "Heap: Error: Line {line} of the outline is not indented by whole levels"),
+			Self::TooDeep { line } => write!(f, "Heap: Error: Line {line} of the outline skips a level of indentation"),
+			Self::SecondRoot { line } => write!(f, "Heap: Error: Line {line} of the outline is a second root"),
+		}
+	}
+}
+
+impl core::error::Error for IndentError {}
+
+impl Heap<String> {
+	/// Parses an outline with one node per line, making each line a child of the closest line
+	/// above it that is indented one level less. The first line is the root and must not be
+	/// indented. Blank lines are skipped, and each value is the rest of its line without the
+	/// indentation or trailing whitespace, so list markers are kept and can be stripped
+	/// afterwards with `map`. Line numbers in errors start at 1.
+	/// Panics if `indent` is `Indent::Spaces(0)`
+	pub fn from_indented(input: &str, indent: Indent) -> Result<Self, IndentError> {
+		let mut unit = match indent {
+			Indent::Detect => None,
+			Indent::Spaces(0) => panic!("Heap: Error: Tried to indent by zero spaces"),
+			Indent::Spaces(width) => Some((' ', width)),
+			Indent::Tabs => Some(('\t', 1)),
+		};
+		let mut heap: Option<Heap<String>> = None;
+		// The most recent node at each level above the current line
+		let mut path: Vec<usize> = Vec::new();
+		for (n, text) in input.lines().enumerate() {
+			let line = n + 1;
+			let value = text.trim_start_matches([' ', '\t']);
+			if value.trim_end().is_empty() {
+				continue;
+			}
+			let leading = &text[..text.len() - value.len()];
+			let level = match leading.chars().next() {
+				None => 0,
+				Some(first) => {
+					let (c, width) = *unit.get_or_insert((first, leading.len()));
+					if leading.chars().any(|l| l != c) {
+						return Err(IndentError::Mixed { line });
+					}
+					if leading.len() % width != 0 {
+						return Err(IndentError::Uneven { line });
+					}
+					leading.len() / width
+				}
+			};
+			if level > path.len() {
+				return Err(IndentError::TooDeep { line });
+			}
+			path.truncate(level);
+			let value = value.trim_end().to_string();
+			let index = match (&mut heap, path.last()) {
+				(None, _) => {
+					heap = Some(Heap::new(value));
+					0
+				}
+				(Some(_), None) => return Err(IndentError::SecondRoot { line }),
+				(Some(heap), Some(&parent)) => heap.insert(value, parent),
+			};
+			path.push(index);
+		}
+		heap.ok_or(IndentError::Empty)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+	use alloc::vec::Vec;
+
+	use super::{Indent, IndentError};
+	use crate::Heap;
+
+	fn shape(heap: &Heap<String>) -> Vec<(&str, Option<usize>)> {
+		heap.iter_dfs(0).map(|(i, v)| (v.as_str(), heap.parent_of(i))).collect()
+	}
+
+	#[test]
+	fn from_indented_test() {
+		let outline = "root\n  - a\n    - a1\n\n    - a2  \n  - b\r\n      \n  - c\n    - c1\n";
+		let heap = Heap::from_indented(outline, Indent::Detect).unwrap();
+		assert_eq!(shape(&heap), [
+			("root", None),
+			("- a", Some(0)),
+			("- a1", Some(1)),
+			("- a2", Some(1)),
+			("- b", Some(0)),
+			("- c", Some(0)),
+			("- c1", Some(5)),
+		]);
+		assert_eq!(Heap::from_indented(outline, Indent::Spaces(2)).unwrap(), heap);
+		let tabs = Heap::from_indented("root\n\ta\n\t\tb\n\tc", Indent::Tabs).unwrap();
+		assert_eq!(shape(&tabs), [("root", None), ("a", Some(0)), ("b", Some(1)), ("c", Some(0))]);
+		let wide = Heap::from_indented("root\n    a\n        b", Indent::Spaces(2)).unwrap_err();
+		assert_eq!(wide, IndentError::TooDeep { line: 2 });
+	}
+
+	#[test]
+	fn from_indented_errors_test() {
+		let parse = |input| Heap::from_indented(input, Indent::Detect);
+		assert_eq!(parse(" \n\n"), Err(IndentError::Empty));
+		assert_eq!(parse("root\n  a\n\tb"), Err(IndentError::Mixed { line: 3 }));
+		assert_eq!(parse("root\n  a\n   b"), Err(IndentError::Uneven { line: 3 }));
+		assert_eq!(parse("root\n  a\n      b"), Err(IndentError::TooDeep { line: 3 }));
+		assert_eq!(parse("  root"), Err(IndentError::TooDeep { line: 1 }));
+		assert_eq!(parse("root\n  a\nother"), Err(IndentError::SecondRoot { line: 3 }));
+		assert_eq!(Heap::from_indented("root\n  a", Indent::Tabs), Err(IndentError::Mixed { line: 2 }));
+	}
+}
@@ -0,0 +1,76 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use indextree::{Arena, NodeId};
+
+use crate::Heap;
+
+impl<T: Clone> Heap<T> {
+	/// Copies the heap into `arena` as a new tree, keeping the order of every node's children,
+	/// and returns the id of its root. Nodes are created in depth-first pre-order.
+	pub fn append_to_indextree(&self, arena: &mut Arena<T>) -> NodeId {
+		let mut ids: Vec<Option<NodeId>> = vec![None; self.inner.len()];
+		let mut root = None;
+		for (i, value) in self.iter_dfs(0) {
+			let id = arena.new_node(value.clone());
+			match self.inner[i].parent() {
+				Some(parent) => ids[parent].expect("Heap: Internal Error: Child visited before parent.").append(id, arena),
+				None => root = Some(id),
+			}
+			ids[i] = Some(id);
+		}
+		root.expect("Heap: Internal Error: Root was not visited.")
+	}
+	/// Copies the heap into a new arena. Returns the arena and the id of the root.
+	pub fn to_indextree(&self) -> (Arena<T>, NodeId) {
+		let mut arena = Arena::with_capacity(self.len());
+		let root = self.append_to_indextree(&mut arena);
+		(arena, root)
+	}
+
+	/// Copies the tree below `root` out of `arena`, numbering the nodes in depth-first
+	/// pre-order and keeping the order of every node's children. The rest of the arena,
+	/// including any parent of `root`, is ignored.
+	/// Panics if `root` has been removed from `arena` or belongs to another arena
+	pub fn from_indextree(arena: &Arena<T>, root: NodeId) -> Self {
+		let node = arena.get(root).filter(|node| !node.is_removed())
+			.expect("Heap: Error: Tried to convert a removed indextree node");
+		let mut heap = Heap::new(node.get().clone());
+		let mut stack: Vec<_> = root.children(arena).map(|c| (c, 0)).collect();
+		stack.reverse();
+		while let Some((id, parent)) = stack.pop() {
+			let index = heap.insert(arena[id].get().clone(), parent);
+			let start = stack.len();
+			stack.extend(id.children(arena).map(|c| (c, index)));
+			stack[start..].reverse();
+		}
+		heap
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use crate::{tree, Heap};
+
+	#[test]
+	fn indextree_round_trip_test() {
+		let mut heap = tree!("root" => ["a" => ["a1", "a2"], "b", "c" => ["c1"]]);
+		heap.move_child(5, 0);
+		let (mut arena, root) = heap.to_indextree();
+		let order: Vec<_> = root.descendants(&arena).map(|id| *arena[id].get()).collect();
+		assert_eq!(order, ["root", "c", "c1", "a", "a1", "a2", "b"]);
+		let back = Heap::from_indextree(&arena, root);
+		assert_eq!(back.iter_dfs(0).map(|(_, &v)| v).collect::<Vec<_>>(), order);
+		assert_eq!(back.children(0).map(|c| back[c]).collect::<Vec<_>>(), ["c", "a", "b"]);
+
+		// Other trees in the same arena, and the parent of the chosen root, are left out
+		let second = heap.append_to_indextree(&mut arena);
+		assert_eq!(arena.len(), 14);
+		let a = root.children(&arena).nth(1).unwrap();
+		let subtree = Heap::from_indextree(&arena, a);
+		assert_eq!(subtree, tree!("a" => ["a1", "a2"]));
+		assert_eq!(Heap::from_indextree(&arena, second).len(), 7);
+	}
+}
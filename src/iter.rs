@@ -0,0 +1,427 @@
+use alloc::collections::VecDeque;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::slice;
+
+use crate::{Heap, Node};
+
+#[cfg(feature = "allocator_api")]
+type NodesIntoIter<T> = vec::IntoIter<Node<T>, crate::HeapAllocator>;
+#[cfg(not(feature = "allocator_api"))]
+type NodesIntoIter<T> = vec::IntoIter<Node<T>>;
+
+/// Iterates over the values of valid nodes in index order
+pub struct Iter<'a, T> {
+	pub(crate) inner: slice::Iter<'a, Node<T>>,
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+	type Item = &'a T;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.inner.by_ref().find_map(|node| node.value.as_ref())
+	}
+}
+
+/// Iterates mutably over the values of valid nodes in index order
+pub struct IterMut<'a, T> {
+	pub(crate) inner: slice::IterMut<'a, Node<T>>,
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+	type Item = &'a mut T;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.inner.by_ref().find_map(|node| node.value.as_mut())
+	}
+}
+
+/// Yields the owned values of valid nodes in index order
+pub struct IntoIter<T> {
+	inner: NodesIntoIter<T>,
+}
+
+impl<T> Iterator for IntoIter<T> {
+	type Item = T;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		self.inner.by_ref().find_map(|node| node.value)
+	}
+}
+
+/// Yields the indices of a node's direct children
+pub struct Children<'a, T> {
+	pub(crate) heap: &'a Heap<T>,
+	// The first and last children not yet yielded from either end
+	pub(crate) front: Option<usize>,
+	pub(crate) back: Option<usize>,
+}
+
+impl<T> Iterator for Children<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let i = self.front?;
+		if self.front == self.back {
+			(self.front, self.back) = (None, None);
+		} else {
+			self.front = self.heap.inner[i].next_sibling.get();
+		}
+		Some(i)
+	}
+}
+
+impl<T> DoubleEndedIterator for Children<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		let i = self.back?;
+		if self.front == self.back {
+			(self.front, self.back) = (None, None);
+		} else {
+			self.back = self.heap.inner[i].prev_sibling.get();
+		}
+		Some(i)
+	}
+}
+
+/// Yields the indices of the other children of a node's parent
+pub struct Siblings<'a, T> {
+	pub(crate) children: Children<'a, T>,
+	pub(crate) index: usize,
+}
+
+impl<T> Iterator for Siblings<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let index = self.index;
+		self.children.find(|&i| i != index)
+	}
+}
+
+impl<T> DoubleEndedIterator for Siblings<'_, T> {
+	fn next_back(&mut self) -> Option<Self::Item> {
+		let index = self.index;
+		self.children.rfind(|&i| i != index)
+	}
+}
+
+/// Yields the indices of a node's descendants in depth-first pre-order
+pub struct Descendants<'a, T> {
+	pub(crate) heap: &'a Heap<T>,
+	pub(crate) start: usize,
+	pub(crate) next: Option<usize>,
+}
+
+impl<T> Iterator for Descendants<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let i = self.next?;
+		self.next = self.heap.pre_order_next(i, self.start);
+		Some(i)
+	}
+}
+
+/// Yields the indices of the nodes without children in a subtree, from left to right
+pub struct Leaves<'a, T> {
+	pub(crate) heap: &'a Heap<T>,
+	pub(crate) start: usize,
+	pub(crate) next: Option<usize>,
+}
+
+impl<T> Iterator for Leaves<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let i = self.next?;
+		// Post-order reaches every leaf in turn, passing only through nodes already left
+		let mut next = self.heap.post_order_next(i, self.start);
+		while let Some(n) = next.filter(|&n| !self.heap.is_leaf(n)) {
+			next = self.heap.post_order_next(n, self.start);
+		}
+		self.next = next;
+		Some(i)
+	}
+}
+
+/// Yields every `(parent, child)` pair of a heap, ordered by the child in depth-first
+/// pre-order. Created by [`Heap::edges`].
+pub struct Edges<'a, T> {
+	pub(crate) descendants: Descendants<'a, T>,
+}
+
+impl<T> Iterator for Edges<'_, T> {
+	type Item = (usize, usize);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let child = self.descendants.next()?;
+		let parent = self.descendants.heap.inner[child].parent().expect("Heap: Internal Error: Orphaned node.");
+		Some((parent, child))
+	}
+}
+
+/// Yields the indices of a node's ancestors, ending with the root
+pub struct Ancestors<'a, T> {
+	pub(crate) heap: &'a Heap<T>,
+	pub(crate) next: Option<usize>,
+}
+
+impl<T> Iterator for Ancestors<'_, T> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let i = self.next?;
+		self.next = self.heap.inner[i].parent();
+		Some(i)
+	}
+}
+
+/// Depth-first pre-order traversal of a subtree, yielding each node's index and value
+pub struct Dfs<'a, T> {
+	pub(crate) heap: &'a Heap<T>,
+	pub(crate) start: usize,
+	pub(crate) next: Option<usize>,
+}
+
+impl<'a, T> Iterator for Dfs<'a, T> {
+	type Item = (usize, &'a T);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let i = self.next?;
+		self.next = self.heap.pre_order_next(i, self.start);
+		Some((i, &self.heap[i]))
+	}
+}
+
+/// Depth-first post-order traversal of a subtree, yielding each node's index and value
+pub struct PostOrder<'a, T> {
+	pub(crate) heap: &'a Heap<T>,
+	pub(crate) start: usize,
+	pub(crate) next: Option<usize>,
+}
+
+impl<'a, T> Iterator for PostOrder<'a, T> {
+	type Item = (usize, &'a T);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let i = self.next?;
+		self.next = self.heap.post_order_next(i, self.start);
+		Some((i, &self.heap[i]))
+	}
+}
+
+/// Breadth-first traversal of a subtree, yielding each node's depth, index and value
+pub struct Bfs<'a, T> {
+	pub(crate) heap: &'a Heap<T>,
+	pub(crate) queue: VecDeque<(usize, usize)>,
+}
+
+impl<'a, T> Iterator for Bfs<'a, T> {
+	type Item = (usize, usize, &'a T);
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let (depth, i) = self.queue.pop_front()?;
+		self.queue.extend(self.heap.children(i).map(|c| (depth + 1, c)));
+		Some((depth, i, &self.heap[i]))
+	}
+}
+
+/// Yields the indices of a subtree's nodes one level at a time, starting with the level of
+/// its root. Each level is ordered from left to right.
+pub struct Levels<'a, T> {
+	pub(crate) heap: &'a Heap<T>,
+	pub(crate) level: Vec<usize>,
+}
+
+impl<T> Iterator for Levels<'_, T> {
+	type Item = Vec<usize>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.level.is_empty() {
+			return None;
+		}
+		let next = self.level.iter().flat_map(|&i| self.heap.children(i)).collect();
+		Some(core::mem::replace(&mut self.level, next))
+	}
+}
+
+/// Yields the indices of the nodes whose values match a predicate, in depth-first pre-order.
+/// Created by [`Heap::find_all`].
+pub struct FindAll<'a, T, P> {
+	pub(crate) dfs: Dfs<'a, T>,
+	pub(crate) predicate: P,
+}
+
+impl<T, P: FnMut(&T) -> bool> Iterator for FindAll<'_, T, P> {
+	type Item = usize;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		let predicate = &mut self.predicate;
+		self.dfs.find_map(|(i, value)| predicate(value).then_some(i))
+	}
+}
+
+impl<T> IntoIterator for Heap<T> {
+	type Item = T;
+	type IntoIter = IntoIter<T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		IntoIter { inner: self.inner.into_iter() }
+	}
+}
+impl<'a, T> IntoIterator for &'a Heap<T> {
+	type Item = &'a T;
+	type IntoIter = Iter<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		Iter { inner: self.inner.iter() }
+	}
+}
+impl<'a, T> IntoIterator for &'a mut Heap<T> {
+	type Item = &'a mut T;
+	type IntoIter = IterMut<'a, T>;
+
+	fn into_iter(self) -> Self::IntoIter {
+		IterMut { inner: self.inner.iter_mut() }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+	use alloc::vec;
+	use alloc::vec::Vec;
+
+	use crate::Heap;
+
+	#[test]
+	fn into_iter_test() {
+		let mut heap = Heap::new(String::from("root"));
+		let a = heap.insert(String::from("a"), 0);
+		heap.insert(String::from("b"), a);
+		heap.insert(String::from("c"), 0);
+		heap.remove(2);
+		for value in &mut heap {
+			value.push('!');
+		}
+		let borrowed: Vec<_> = (&heap).into_iter().map(String::as_str).collect();
+		assert_eq!(borrowed, vec!["root!", "a!", "c!"]);
+		let owned: Vec<String> = heap.into_iter().collect();
+		assert_eq!(owned, vec!["root!", "a!", "c!"]);
+	}
+
+	#[test]
+	fn children_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let b = heap.insert("b", 0);
+		let a1 = heap.insert("a1", a);
+		heap.insert("c", 0);
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![a, b, 4]);
+		assert_eq!(heap.children(a).collect::<Vec<_>>(), vec![a1]);
+		assert_eq!(heap.children(a1).next(), None);
+		heap.remove(b);
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![a, 4]);
+		let mut children = heap.children(0);
+		assert_eq!((children.next_back(), children.next(), children.next()), (Some(4), Some(a), None));
+	}
+
+	#[test]
+	fn siblings_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let b = heap.insert("b", 0);
+		let c = heap.insert("c", 0);
+		let a1 = heap.insert("a1", a);
+		assert_eq!(heap.siblings_of(b).collect::<Vec<_>>(), vec![a, c]);
+		assert_eq!(heap.siblings_of(a).rev().collect::<Vec<_>>(), vec![c, b]);
+		assert_eq!(heap.siblings_of(a1).next(), None);
+		assert_eq!(heap.siblings_of(0).next(), None);
+	}
+
+	#[test]
+	fn descendants_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let b = heap.insert("b", 0);
+		let a1 = heap.insert("a1", a);
+		let b1 = heap.insert("b1", b);
+		let a1x = heap.insert("a1x", a1);
+		let a2 = heap.insert("a2", a);
+		assert_eq!(heap.descendants(a).collect::<Vec<_>>(), vec![a1, a1x, a2]);
+		assert_eq!(heap.descendants(0).collect::<Vec<_>>(), vec![a, a1, a1x, a2, b, b1]);
+		assert_eq!(heap.descendants(a2).next(), None);
+	}
+
+	#[test]
+	fn ancestors_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let a1 = heap.insert("a1", a);
+		let a1x = heap.insert("a1x", a1);
+		assert_eq!(heap.ancestors(a1x).collect::<Vec<_>>(), vec![a1, a, 0]);
+		assert_eq!(heap.ancestors(a1x).map(|i| heap[i]).collect::<Vec<_>>(), vec!["a1", "a", "root"]);
+		assert_eq!(heap.ancestors(0).next(), None);
+		assert_eq!(heap.ancestors(42).next(), None);
+	}
+
+	#[test]
+	fn dfs_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let b = heap.insert("b", 0);
+		let a1 = heap.insert("a1", a);
+		heap.insert("b1", b);
+		heap.insert("a2", a);
+		heap.insert("a1x", a1);
+		let order: Vec<_> = heap.iter_dfs(0).map(|(_, v)| *v).collect();
+		assert_eq!(order, vec!["root", "a", "a1", "a1x", "a2", "b", "b1"]);
+		let order: Vec<_> = heap.iter_dfs(a).map(|(i, _)| i).collect();
+		assert_eq!(order, vec![a, a1, 6, 5]);
+	}
+
+	#[test]
+	fn post_order_test() {
+		let mut heap = Heap::new(1);
+		let a = heap.insert(2, 0);
+		let b = heap.insert(3, 0);
+		heap.insert(4, a);
+		heap.insert(5, a);
+		heap.insert(6, b);
+		let order: Vec<_> = heap.iter_post_order(0).map(|(_, v)| *v).collect();
+		assert_eq!(order, vec![4, 5, 2, 6, 3, 1]);
+		let mut sizes = vec![0; 6];
+		for (i, _) in heap.iter_post_order(0) {
+			sizes[i] = 1 + heap.direct_children_of(i).iter().map(|&c| sizes[c]).sum::<usize>();
+		}
+		assert_eq!(sizes, vec![6, 3, 2, 1, 1, 1]);
+	}
+
+	#[test]
+	fn find_test() {
+		let mut heap = Heap::new(0);
+		let a = heap.insert(1, 0);
+		let b = heap.insert(2, 0);
+		let a1 = heap.insert(3, a);
+		let b1 = heap.insert(4, b);
+		heap.move_subtree(a1, b);
+		assert_eq!(heap.find(|&v| v > 2), Some(b1));
+		assert_eq!(heap.find(|&v| v > 4), None);
+		assert_eq!(heap.find_all(|&v| v % 2 == 1).collect::<Vec<_>>(), vec![a, a1]);
+		assert_eq!(heap.find_all(|&v| v >= 2).collect::<Vec<_>>(), vec![b, b1, a1]);
+	}
+
+	#[test]
+	fn bfs_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let b = heap.insert("b", 0);
+		let a1 = heap.insert("a1", a);
+		heap.insert("b1", b);
+		heap.insert("a1x", a1);
+		let order: Vec<_> = heap.iter_bfs(0).map(|(d, _, v)| (d, *v)).collect();
+		assert_eq!(order, vec![(0, "root"), (1, "a"), (1, "b"), (2, "a1"), (2, "b1"), (3, "a1x")]);
+		let order: Vec<_> = heap.iter_bfs(a).map(|(d, i, _)| (d, i)).collect();
+		assert_eq!(order, vec![(0, a), (1, a1), (2, 5)]);
+	}
+}
@@ -0,0 +1,203 @@
+use alloc::vec::Vec;
+
+use crate::Heap;
+
+// A primitive edit. Applying one returns the edit that reverses it.
+#[derive(Clone, Debug)]
+enum Op<T> {
+	Remove(usize),
+	// Nodes in pre-order as (index, parent, value). The first is placed right after `prev`,
+	// and the rest are appended to their parents in turn.
+	Restore { prev: Option<usize>, nodes: Vec<(usize, usize, T)> },
+	Move { index: usize, parent: usize, prev: Option<usize> },
+	Replace { index: usize, value: T },
+}
+
+impl<T> Op<T> {
+	fn apply(self, heap: &mut Heap<T>) -> Op<T> {
+		match self {
+			Op::Remove(index) => {
+				let prev = heap.inner[index].prev_sibling.get();
+				let order = heap.subtree(index);
+				let parents: Vec<_> = order.iter()
+					.map(|&i| heap.inner[i].parent().expect("Heap: Internal Error: Orphaned node."))
+					.collect();
+				let values = heap.remove(index);
+				let nodes = order.into_iter().zip(parents).zip(values).map(|((i, p), v)| (i, p, v)).collect();
+				Op::Restore { prev, nodes }
+			}
+			Op::Restore { prev, nodes } => {
+				let index = nodes[0].0;
+				for (n, (i, parent, value)) in nodes.into_iter().enumerate() {
+					let prev = match n {
+						0 => prev,
+						_ => heap.inner[parent].last_child.get(),
+					};
+					heap.insert_at(i, value, parent, prev);
+				}
+				Op::Remove(index)
+			}
+			Op::Move { index, parent, prev } => {
+				let node = &heap.inner[index];
+				let old_parent = node.parent().expect("Heap: Internal Error: Orphaned node.");
+				let inverse = Op::Move { index, parent: old_parent, prev: node.prev_sibling.get() };
+				heap.relink(index, parent, prev);
+				inverse
+			}
+			Op::Replace { index, value } => Op::Replace { index, value: heap.replace(index, value) },
+		}
+	}
+}
+
+/// A heap that records every edit made through it, so that edits can be undone and redone.
+/// Undoing restores removed nodes at their original indices and sibling positions, so the
+/// indices of later edits stay meaningful. Restored nodes get fresh `NodeId`s.
+#[derive(Clone, Debug)]
+pub struct Journal<T> {
+	heap: Heap<T>,
+	undo: Vec<Op<T>>,
+	redo: Vec<Op<T>>,
+}
+
+/// A position in a journal's undo history. Created by [`Journal::savepoint`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Savepoint(usize);
+
+impl<T> Journal<T> {
+	pub fn new(heap: Heap<T>) -> Self {
+		Self { heap, undo: Vec::new(), redo: Vec::new() }
+	}
+	pub fn heap(&self) -> &Heap<T> {
+		&self.heap
+	}
+	pub fn into_heap(self) -> Heap<T> {
+		self.heap
+	}
+
+	/// Panics if `parent` is not the index of a valid node
+	pub fn insert(&mut self, node: T, parent: usize) -> usize {
+		let index = self.heap.insert(node, parent);
+		self.record(Op::Remove(index));
+		index
+	}
+	/// Removes the node at `index` along with all of its descendants.
+	/// Panics if `index` is invalid.
+	/// Panics if `index` is 0.
+	pub fn remove(&mut self, index: usize) {
+		assert!(index > 0, "Heap: Error: Tried to remove the root node.");
+		assert!(self.heap.is_valid_idx(index), "Heap: Error: Tried to remove an invalid node");
+		let inverse = Op::Remove(index).apply(&mut self.heap);
+		self.record(inverse);
+	}
+	/// Panics under the same conditions as `Heap::move_subtree`
+	pub fn move_subtree(&mut self, index: usize, new_parent: usize) {
+		let old = self.heap.inner.get(index).map(|node| (node.parent(), node.prev_sibling.get()));
+		self.heap.move_subtree(index, new_parent);
+		let (parent, prev) = old.expect("Heap: Internal Error: Moved an invalid node.");
+		let parent = parent.expect("Heap: Internal Error: Moved the root node.");
+		self.record(Op::Move { index, parent, prev });
+	}
+	/// Panics if `index` is invalid
+	pub fn replace(&mut self, index: usize, value: T) {
+		let value = self.heap.replace(index, value);
+		self.record(Op::Replace { index, value });
+	}
+	// A new edit makes the undone ones unreachable
+	fn record(&mut self, inverse: Op<T>) {
+		self.undo.push(inverse);
+		self.redo.clear();
+	}
+
+	/// Reverts the most recent edit. Returns `false` if there was nothing to undo.
+	pub fn undo(&mut self) -> bool {
+		let Some(op) = self.undo.pop() else {
+			return false;
+		};
+		let inverse = op.apply(&mut self.heap);
+		self.redo.push(inverse);
+		true
+	}
+	/// Reapplies the most recently undone edit. Returns `false` if there was nothing to redo.
+	pub fn redo(&mut self) -> bool {
+		let Some(op) = self.redo.pop() else {
+			return false;
+		};
+		let inverse = op.apply(&mut self.heap);
+		self.undo.push(inverse);
+		true
+	}
+	pub fn can_undo(&self) -> bool {
+		!self.undo.is_empty()
+	}
+	pub fn can_redo(&self) -> bool {
+		!self.redo.is_empty()
+	}
+	/// Forgets every recorded edit, keeping the heap as it is
+	pub fn clear_history(&mut self) {
+		self.undo.clear();
+		self.redo.clear();
+	}
+
+	pub fn savepoint(&self) -> Savepoint {
+		Savepoint(self.undo.len())
+	}
+	/// Undoes every edit made since `savepoint`. The undone edits can still be redone.
+	pub fn rollback(&mut self, savepoint: Savepoint) {
+		while self.undo.len() > savepoint.0 {
+			self.undo();
+		}
+	}
+}
+
+impl<T> From<Heap<T>> for Journal<T> {
+	fn from(heap: Heap<T>) -> Self {
+		Self::new(heap)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+	use alloc::vec::Vec;
+
+	use super::Journal;
+	use crate::{tree, Heap};
+
+	fn shape(heap: &Heap<&'static str>) -> Vec<(usize, &'static str, Option<usize>)> {
+		heap.iter_dfs(0).map(|(i, v)| (i, *v, heap.parent_of(i))).collect()
+	}
+
+	#[test]
+	fn undo_redo_test() {
+		let heap = tree!("root" => ["a" => ["a1", "a2"], "b", "c"]);
+		let original = shape(&heap);
+		let mut journal = Journal::new(heap);
+		let d = journal.insert("d", 4);
+		journal.move_subtree(1, 5);
+		journal.replace(1, "renamed");
+		journal.remove(5);
+		assert_eq!(journal.heap().len(), 3);
+		let edited = shape(journal.heap());
+		let savepoint = journal.savepoint();
+		journal.remove(4);
+
+		journal.rollback(savepoint);
+		assert_eq!(shape(journal.heap()), edited);
+		while journal.undo() {}
+		assert_eq!(shape(journal.heap()), original);
+		assert!(!journal.can_undo());
+
+		while journal.redo() {}
+		assert_eq!(journal.heap().len(), 1);
+		journal.undo();
+		assert_eq!(shape(journal.heap()), edited);
+		assert!(journal.heap().is_valid_idx(d));
+		journal.undo();
+		assert_eq!(journal.heap()[1], "renamed");
+		assert_eq!(journal.heap().children(5).collect::<Vec<_>>(), vec![1]);
+		journal.undo();
+		assert_eq!(journal.heap()[1], "a");
+		journal.insert("e", 0);
+		assert!(!journal.can_redo());
+	}
+}
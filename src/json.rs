@@ -0,0 +1,83 @@
+use serde::de::{DeserializeOwned, Error as _};
+use serde::Serialize;
+use serde_json::{Error, Map, Value};
+
+use crate::Heap;
+
+// Every node is represented as `{ "value": ..., "children": [...] }`,
+// with children in the same order as `Heap::children`
+
+impl<T: Serialize> Heap<T> {
+	pub fn to_json_tree(&self) -> Result<Value, Error> {
+		let mut built: Vec<Option<Value>> = vec![None; self.inner.len()];
+		for (i, value) in self.iter_post_order(0) {
+			let mut node = Map::new();
+			node.insert(String::from("value"), serde_json::to_value(value)?);
+			let children = self.children(i)
+				.map(|c| built[c].take().expect("Heap: Internal Error: Child visited before parent."))
+				.collect();
+			node.insert(String::from("children"), Value::Array(children));
+			built[i] = Some(Value::Object(node));
+		}
+		Ok(built[0].take().expect("Heap: Internal Error: Root was not visited."))
+	}
+}
+
+impl<T: DeserializeOwned> Heap<T> {
+	/// A missing `"children"` field is treated as an empty list
+	pub fn from_json_tree(json: Value) -> Result<Self, Error> {
+		let (root, children) = split_node(json)?;
+		let mut heap = Heap::new(root);
+		let mut stack: Vec<_> = children.into_iter().rev().map(|c| (c, 0)).collect();
+		while let Some((json, parent)) = stack.pop() {
+			let (value, children) = split_node(json)?;
+			let index = heap.insert(value, parent);
+			stack.extend(children.into_iter().rev().map(|c| (c, index)));
+		}
+		Ok(heap)
+	}
+}
+
+fn split_node<T: DeserializeOwned>(json: Value) -> Result<(T, Vec<Value>), Error> {
+	let Value::Object(mut node) = json else {
+		return Err(Error::custom("expected a node object"));
+	};
+	let value = node.remove("value").ok_or_else(|| Error::custom("node is missing a value"))?;
+	let children = match node.remove("children") {
+		None => Vec::new(),
+		Some(Value::Array(children)) => children,
+		Some(_) => return Err(Error::custom("node children must be an array")),
+	};
+	Ok((serde_json::from_value(value)?, children))
+}
+
+#[cfg(test)]
+mod tests {
+	use serde_json::json;
+
+	use crate::Heap;
+
+	#[test]
+	fn json_tree_round_trip_test() {
+		let mut heap = Heap::new(1);
+		let a = heap.insert(2, 0);
+		heap.insert(3, a);
+		heap.insert(4, 0);
+		let json = heap.to_json_tree().unwrap();
+		assert_eq!(json, json!({ "value": 1, "children": [
+			{ "value": 2, "children": [{ "value": 3, "children": [] }] },
+			{ "value": 4, "children": [] },
+		]}));
+		let back = Heap::<i32>::from_json_tree(json.clone()).unwrap();
+		assert_eq!(back.to_json_tree().unwrap(), json);
+	}
+
+	#[test]
+	fn from_json_tree_errors_test() {
+		assert!(Heap::<i32>::from_json_tree(json!({ "value": 1 })).is_ok());
+		assert!(Heap::<i32>::from_json_tree(json!([1])).is_err());
+		assert!(Heap::<i32>::from_json_tree(json!({ "children": [] })).is_err());
+		assert!(Heap::<i32>::from_json_tree(json!({ "value": 1, "children": {} })).is_err());
+		assert!(Heap::<i32>::from_json_tree(json!({ "value": "one" })).is_err());
+	}
+}
@@ -0,0 +1,100 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+
+use crate::Heap;
+
+/// Answers lowest common ancestor queries in O(1) after O(n log n) preprocessing, using an
+/// Euler tour of the heap and a sparse table of its minimum depths.
+/// The index borrows the heap it was built from, so it can never go out of date; build a new
+/// one after mutating the heap.
+#[derive(Clone, Debug)]
+pub struct LcaIndex<'a> {
+	// Every node in the order the tour passes through it, with the node's depth
+	tour: Vec<(usize, usize)>,
+	// Where each valid node first appears in the tour
+	first: Vec<Option<usize>>,
+	// `table[k][p]` is the shallowest position of the tour in `p..p + 2^k`
+	table: Vec<Vec<usize>>,
+	heap: PhantomData<&'a ()>,
+}
+
+impl<'a> LcaIndex<'a> {
+	pub fn build<T>(heap: &'a Heap<T>) -> Self {
+		let mut tour = Vec::with_capacity(2 * heap.len());
+		let mut first = vec![None; heap.inner.len()];
+		let mut path: Vec<usize> = Vec::new();
+		for (i, _) in heap.iter_dfs(0) {
+			if let Some(parent) = heap.inner[i].parent() {
+				// Climbing back up passes through each ancestor again
+				while path.last() != Some(&parent) {
+					path.pop();
+					let ancestor = *path.last().expect("Heap: Internal Error: Parent was not on the path.");
+					tour.push((ancestor, path.len() - 1));
+				}
+			}
+			first[i] = Some(tour.len());
+			tour.push((i, path.len()));
+			path.push(i);
+		}
+
+		let mut table = vec![(0..tour.len()).collect::<Vec<_>>()];
+		let mut width = 1;
+		while 2 * width <= tour.len() {
+			let last = table.last().expect("Heap: Internal Error: Sparse table has no levels.");
+			let level = (0..=tour.len() - 2 * width)
+				.map(|p| {
+					let (a, b) = (last[p], last[p + width]);
+					if tour[b].1 < tour[a].1 { b } else { a }
+				})
+				.collect();
+			table.push(level);
+			width *= 2;
+		}
+		Self { tour, first, table, heap: PhantomData }
+	}
+
+	/// Returns the deepest node that has both `a` and `b` in its subtree, like
+	/// `Heap::lowest_common_ancestor`.
+	/// Panics if `a` or `b` is invalid
+	pub fn lca(&self, a: usize, b: usize) -> usize {
+		let position = |i: usize| self.first.get(i).copied().flatten();
+		let (Some(a), Some(b)) = (position(a), position(b)) else {
+			panic!("Heap: Error: Tried to find the common ancestor of invalid nodes");
+		};
+		let (start, end) = (a.min(b), a.max(b) + 1);
+		let k = (end - start).ilog2() as usize;
+		let (a, b) = (self.table[k][start], self.table[k][end - (1 << k)]);
+		let shallowest = if self.tour[b].1 < self.tour[a].1 { b } else { a };
+		self.tour[shallowest].0
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::LcaIndex;
+	use crate::{tree, Heap};
+
+	#[test]
+	fn lca_index_test() {
+		let mut heap = tree!("root" => ["a" => ["a1" => ["a11"], "a2"], "b" => ["b1"]]);
+		heap.remove(6);
+		heap.insert("c", 0);
+		let index = LcaIndex::build(&heap);
+		for a in 0..heap.len() {
+			for b in 0..heap.len() {
+				assert_eq!(index.lca(a, b), heap.lowest_common_ancestor(a, b));
+			}
+		}
+		assert_eq!(index.lca(3, 4), 1);
+		assert_eq!(LcaIndex::build(&Heap::new(())).lca(0, 0), 0);
+	}
+
+	#[test]
+	#[should_panic]
+	fn lca_index_invalid_test() {
+		let mut heap = tree!("root" => ["a", "b"]);
+		heap.remove(2);
+		LcaIndex::build(&heap).lca(1, 2);
+	}
+}
@@ -1,107 +1,1398 @@
-use std::collections::{TryReserveError, HashSet};
-use std::ops::{Index, IndexMut};
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 
+extern crate alloc;
+
+use alloc::collections::{TryReserveError, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::mem::size_of;
+use core::num::NonZero;
+use core::ops::{Index, IndexMut, Range};
+
+mod aggregate;
+mod ancestry;
+#[cfg(feature = "allocator_api")]
+mod alloc_api;
+#[cfg(feature = "arbitrary")]
+mod arbitrary_impl;
+mod build;
+mod cursor;
+mod diff;
+mod dot;
+#[cfg(feature = "edit_distance")]
+mod edit_distance;
+#[cfg(feature = "ego-tree")]
+mod ego_tree_impl;
+mod free;
+#[cfg(feature = "graphml")]
+mod graphml;
+mod heap_map;
+mod html;
+#[cfg(feature = "indextree")]
+mod indextree_impl;
+mod indented;
+mod iter;
+mod journal;
+#[cfg(feature = "json")]
+mod json;
+mod lca;
+mod macros;
+mod mermaid;
+mod nested;
+#[cfg(feature = "newick")]
+mod newick;
+mod node_ref;
+mod observer;
+#[cfg(feature = "rayon")]
+mod par;
+mod patch;
+mod path;
+mod persistent;
+#[cfg(feature = "petgraph")]
+mod petgraph_impl;
+mod pretty;
+#[cfg(feature = "proptest")]
+mod proptest_impl;
+#[cfg(feature = "rkyv")]
+mod rkyv_impl;
+#[cfg(feature = "serde")]
+mod serde_impl;
+mod sexpr;
+#[cfg(feature = "slotmap")]
+mod slotmap_impl;
+mod snapshot;
+mod stats;
+mod transform;
+mod walk;
+mod weak;
+
+pub use aggregate::{Monoid, SubtreeAggregate};
+use ancestry::PreOrder;
+#[cfg(feature = "allocator_api")]
+pub use alloc_api::HeapAllocator;
+pub use build::{EdgeError, HeapBuilder, ParentArrayError};
+pub use cursor::{Cursor, CursorMut};
+pub use diff::{EditNode, TreeEdit};
+#[cfg(feature = "edit_distance")]
+pub use edit_distance::EditDistance;
+use free::FreeList;
+pub use heap_map::HeapMap;
+pub use indented::{Indent, IndentError};
+pub use journal::{Journal, Savepoint};
+pub use lca::LcaIndex;
+pub use iter::{
+	Ancestors, Bfs, Children, Descendants, Dfs, Edges, FindAll, IntoIter, Iter, IterMut, Leaves, Levels, PostOrder,
+	Siblings,
+};
+pub use nested::TreeNode;
+#[cfg(feature = "newick")]
+pub use newick::{NewickError, NewickNode};
+pub use node_ref::{NodeMut, NodeRef};
+use observer::Observer;
+pub use observer::{BoxedObserver, HeapObserver};
+pub use patch::PatchError;
+pub use persistent::{PersistentHeap, PersistentNode};
+#[cfg(feature = "petgraph")]
+pub use petgraph_impl::GraphTreeError;
+pub use pretty::Pretty;
+#[cfg(feature = "proptest")]
+pub use proptest_impl::{heap_strategy, HeapParams};
+#[cfg(feature = "rkyv")]
+pub use rkyv_impl::ArchivedHeap;
+pub use sexpr::SexprError;
+#[cfg(feature = "slotmap")]
+pub use slotmap_impl::NodeKey;
+pub use snapshot::HeapSnapshot;
+pub use stats::HeapStats;
+pub use walk::{Visitor, WalkControl};
+pub use weak::WeakNodeId;
+use weak::Watchers;
+
+#[cfg(feature = "std")]
+pub(crate) type Map<K, V> = std::collections::HashMap<K, V>;
+#[cfg(feature = "std")]
+pub(crate) type Set<T> = std::collections::HashSet<T>;
+#[cfg(not(feature = "std"))]
+pub(crate) type Map<K, V> = alloc::collections::BTreeMap<K, V>;
+#[cfg(not(feature = "std"))]
+pub(crate) type Set<T> = alloc::collections::BTreeSet<T>;
+
+/// A set of node indices: a `HashSet` with the `std` feature, and a `BTreeSet` without it
+pub type IndexSet = Set<usize>;
+/// A map from keys to node indices: a `HashMap` with the `std` feature, and a `BTreeMap` without it
+pub type IndexMap<K = usize> = Map<K, usize>;
+
+/// Keys usable in an [`IndexMap`]: `Hash + Eq` with the `std` feature, and `Ord` without it
+#[cfg(feature = "std")]
+pub trait Key: Hash + Eq + Clone {}
+#[cfg(feature = "std")]
+impl<K: Hash + Eq + Clone> Key for K {}
+#[cfg(not(feature = "std"))]
+pub trait Key: Ord + Clone {}
+#[cfg(not(feature = "std"))]
+impl<K: Ord + Clone> Key for K {}
+
+// Lets exported macros reach `alloc` from crates that don't link it themselves
+#[doc(hidden)]
+pub mod __private {
+	pub use alloc::vec;
+}
+
+#[cfg(feature = "allocator_api")]
+type Nodes<T> = Vec<Node<T>, HeapAllocator>;
+#[cfg(not(feature = "allocator_api"))]
+type Nodes<T> = Vec<Node<T>>;
+#[cfg(feature = "allocator_api")]
+use alloc_api::nodes_with_capacity;
+#[cfg(not(feature = "allocator_api"))]
+fn nodes_with_capacity<T>(capacity: usize) -> Nodes<T> {
+	Vec::with_capacity(capacity)
+}
 
 #[derive(Clone,Debug)]
 pub struct Heap<T> {
-	inner: Vec<(T, Option<usize>)>,
-	free: HashSet<usize>,
+	inner: Nodes<T>,
+	// Freed slots below `inner.len()`; every index past the end is implicitly free
+	free: FreeList,
 	len: usize,
+	// The generation given to newly created slots, raised by `compact` so that
+	// no `NodeId` from before the compaction can resolve again
+	min_generation: u32,
+	watchers: Watchers,
+	observer: Observer,
+	pre_order: PreOrder,
+}
+
+// Indices stored inside nodes, which are narrowed to 32 bits by the `compact_index` feature
+#[cfg(feature = "compact_index")]
+type RawIndex = u32;
+#[cfg(not(feature = "compact_index"))]
+type RawIndex = usize;
+
+/// The largest index a node can occupy, which is lowered to `u32::MAX - 1` by the `compact_index` feature
+#[allow(clippy::unnecessary_cast)]
+pub const MAX_INDEX: usize = match RawIndex::MAX as u128 - 1 > usize::MAX as u128 {
+	true => usize::MAX,
+	false => RawIndex::MAX as usize - 1,
+};
+
+#[cfg(feature = "compact_index")]
+fn fits_raw_index(index: usize) -> bool {
+	index <= MAX_INDEX
+}
+#[cfg(not(feature = "compact_index"))]
+fn fits_raw_index(_: usize) -> bool {
+	true
+}
+
+// An optional index, stored offset by one so that `None` takes no extra space
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Link(Option<NonZero<RawIndex>>);
+
+// Every index is checked against `MAX_INDEX` when its slot is taken, so the casts are lossless
+#[allow(clippy::unnecessary_cast)]
+impl Link {
+	const NONE: Self = Self(None);
+
+	fn new(index: Option<usize>) -> Self {
+		Self(index.and_then(|i| NonZero::new(i as RawIndex + 1)))
+	}
+	fn get(self) -> Option<usize> {
+		self.0.map(|i| i.get() as usize - 1)
+	}
+}
+
+#[derive(Clone, Debug)]
+struct Node<T> {
+	// `None` once the slot has been freed
+	value: Option<T>,
+	parent: Link,
+	// Children form a doubly linked list, so they can be visited and unlinked without a scan
+	first_child: Link,
+	last_child: Link,
+	prev_sibling: Link,
+	next_sibling: Link,
+	// The length of the child list, kept so that counting children needs no walk
+	child_count: RawIndex,
+	// The number of nodes in the subtree rooted here, including this one
+	size: RawIndex,
+	// The number of edges between this node and the root
+	depth: RawIndex,
+	// Bumped every time the slot is freed, so stale `NodeId`s stop resolving
+	generation: u32,
+}
+
+impl<T> Node<T> {
+	fn new(value: T, generation: u32) -> Self {
+		Self {
+			value: Some(value),
+			parent: Link::NONE,
+			first_child: Link::NONE,
+			last_child: Link::NONE,
+			prev_sibling: Link::NONE,
+			next_sibling: Link::NONE,
+			child_count: 0,
+			size: 1,
+			depth: 0,
+			generation,
+		}
+	}
+	fn parent(&self) -> Option<usize> {
+		self.parent.get()
+	}
+	// The same slot with its links and generation, but holding `value` instead
+	fn with_value<U>(&self, value: Option<U>) -> Node<U> {
+		Node {
+			value,
+			parent: self.parent,
+			first_child: self.first_child,
+			last_child: self.last_child,
+			prev_sibling: self.prev_sibling,
+			next_sibling: self.next_sibling,
+			child_count: self.child_count,
+			size: self.size,
+			depth: self.depth,
+			generation: self.generation,
+		}
+	}
+	fn clear_links(&mut self) {
+		self.parent = Link::NONE;
+		self.first_child = Link::NONE;
+		self.last_child = Link::NONE;
+		self.prev_sibling = Link::NONE;
+		self.next_sibling = Link::NONE;
+		self.child_count = 0;
+	}
+	// Rewrites every link through `new_index`, for when nodes change slots
+	fn remap_links(&mut self, new_index: impl Fn(usize) -> usize) {
+		for link in [
+			&mut self.parent,
+			&mut self.first_child,
+			&mut self.last_child,
+			&mut self.prev_sibling,
+			&mut self.next_sibling,
+		] {
+			*link = Link::new(link.get().map(&new_index));
+		}
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HeapError {
+	/// The given parent is not the index of a valid node
+	InvalidParent(usize),
+	/// The given index is not the index of a valid node
+	InvalidIndex(usize),
+	/// The operation would remove the root node
+	RootRemoval,
+	/// The operation would give the root node a parent
+	RootReparent,
+	/// The operation would make a node its own ancestor
+	Cycle,
+	/// The operation would place a node beyond `MAX_INDEX`
+	CapacityOverflow,
+	/// The same index was given more than once where distinct nodes are required
+	DuplicateIndex(usize),
+	/// The operation would place a node beside the root node
+	RootSibling,
+}
+
+impl fmt::Display for HeapError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::InvalidParent(i) => write!(f, "Heap: Error: {i} is not a valid parent"),
+			Self::InvalidIndex(i) => write!(f, "Heap: Error: {i} is not a valid index"),
+			Self::RootRemoval => write!(f, "Heap: Error: Tried to remove the root node"),
+			Self::RootReparent => write!(f, "Heap: Error: Tried to give the root node a parent"),
+			Self::Cycle => write!(f, "Heap: Error: Tried to move a node under its own descendant"),
+			Self::CapacityOverflow => write!(f, "Heap: Error: Ran out of indices"),
+			Self::DuplicateIndex(i) => write!(f, "Heap: Error: {i} was given more than once"),
+			Self::RootSibling => write!(f, "Heap: Error: Tried to give the root node a sibling"),
+		}
+	}
+}
+
+impl core::error::Error for HeapError {}
+
+/// A handle to a node that, unlike a raw index, stops resolving once the node is removed,
+/// even if its slot is later reused by another node.
+///
+/// `insert` and the other index-based methods keep taking and returning `usize`, since
+/// every traversal, view and format in the crate speaks in indices and switching them all
+/// would break each caller. Those indices are only checked for pointing at some valid node,
+/// so code that holds on to nodes across removals should use `insert_id`, `id_of`,
+/// `resolve` and `get_by_id`, which fail cleanly once the generation no longer matches.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId {
+	index: usize,
+	generation: u32,
+}
+
+impl NodeId {
+	/// The raw index this handle was created for. It may no longer be valid.
+	pub fn index(&self) -> usize {
+		self.index
+	}
+	pub fn generation(&self) -> u32 {
+		self.generation
+	}
 }
 
 // Assumptions:
-// 	Where P is the parent of node C, the index of P < the index of C
+// 	Parent and child indices are unordered, since nodes can be moved under any other node
 // 	The only valid node with parent `None` is the root node
 // 	The root node cannot be invalidated or removed
 // 	Therefore, the node with the index `0` is the root node
 // 	Every node has a valid parent
+// 	A slot is free exactly when its value is `None`
+// 	Every valid node appears exactly once in its parent's child list, and free slots have no links
 
-impl<T: std::fmt::Debug> Heap<T> {
+impl<T> Heap<T> {
 	pub fn new(root: T) -> Self {
-		Self {
-			inner: vec![(root, None)],
-			free: HashSet::from([1]),
-			len: 1,
-		}
+		Self::with_capacity(1, root)
 	}
 	pub fn with_capacity(capacity: usize, root: T) -> Self {
-		let mut inner = Vec::with_capacity(capacity);
-		inner.push((root, None));
+		Self::with_nodes(nodes_with_capacity(capacity), root)
+	}
+	fn with_nodes(mut inner: Nodes<T>, root: T) -> Self {
+		inner.push(Node::new(root, 0));
 		Self {
 			inner,
-			free: HashSet::from([1]),
+			free: FreeList::new(),
 			len: 1,
+			min_generation: 0,
+			watchers: Watchers::default(),
+			observer: Observer::default(),
+			pre_order: PreOrder::default(),
 		}
 	}
+	#[cfg(not(feature = "allocator_api"))]
+	fn sibling_nodes<U>(&self, capacity: usize) -> Nodes<U> {
+		nodes_with_capacity(capacity)
+	}
 
+	/// Places the node in the lowest free index, reusing removed slots before growing.
 	/// Panics if `parent` is not the index of a valid node
 	pub fn insert(&mut self, node: T, parent: usize) -> usize {
 		assert!(
 			self.is_valid_idx(parent),
 			"Heap: Error: Tried to insert with invalid parent"
 		);
-		let i = *self.free.iter().skip_while(|x| x <= &&parent).next()
-			.expect("Heap: Internal Error: Missing trailing free index.");
-		self.free.remove(&i);
-		if i >= self.len() {
-			self.free.insert(i + 1);
-			self.inner.push((node, Some(parent)));
+		self.insert_unchecked(node, parent)
+	}
+	pub fn try_insert(&mut self, node: T, parent: usize) -> Result<usize, HeapError> {
+		if !self.is_valid_idx(parent) {
+			return Err(HeapError::InvalidParent(parent));
+		}
+		if !fits_raw_index(self.next_free()) {
+			return Err(HeapError::CapacityOverflow);
+		}
+		Ok(self.insert_unchecked(node, parent))
+	}
+	/// Appends every value in `nodes` as a child of `parent`, in order, reserving space once.
+	/// The new nodes always go in new slots past the end rather than in free ones, so their
+	/// indices form the returned range.
+	/// Panics if `parent` is not the index of a valid node
+	pub fn insert_children<I: IntoIterator<Item = T>>(&mut self, parent: usize, nodes: I) -> Range<usize> {
+		assert!(self.is_valid_idx(parent), "Heap: Error: Tried to insert with invalid parent");
+		let nodes = nodes.into_iter();
+		self.inner.reserve(nodes.size_hint().0);
+		let start = self.inner.len();
+		for node in nodes {
+			let i = self.inner.len();
+			assert!(fits_raw_index(i), "Heap: Error: Ran out of indices");
+			self.insert_at(i, node, parent, self.inner[parent].last_child.get());
+		}
+		start..self.inner.len()
+	}
+	/// Inserts `node` among the children of `sibling`'s parent, right before `sibling`.
+	/// Panics if `sibling` is invalid.
+	/// Panics if `sibling` is 0.
+	pub fn insert_before(&mut self, node: T, sibling: usize) -> usize {
+		self.try_insert_before(node, sibling).unwrap_or_else(|e| panic!("{e}"))
+	}
+	pub fn try_insert_before(&mut self, node: T, sibling: usize) -> Result<usize, HeapError> {
+		let parent = self.parent_for_sibling(sibling)?;
+		self.try_insert_after_child(node, parent, self.inner[sibling].prev_sibling.get())
+	}
+	/// Inserts `node` among the children of `sibling`'s parent, right after `sibling`.
+	/// Panics if `sibling` is invalid.
+	/// Panics if `sibling` is 0.
+	pub fn insert_after(&mut self, node: T, sibling: usize) -> usize {
+		self.try_insert_after(node, sibling).unwrap_or_else(|e| panic!("{e}"))
+	}
+	pub fn try_insert_after(&mut self, node: T, sibling: usize) -> Result<usize, HeapError> {
+		let parent = self.parent_for_sibling(sibling)?;
+		self.try_insert_after_child(node, parent, Some(sibling))
+	}
+	/// Inserts `node` in the place of `index` among its siblings and moves `index` under it,
+	/// so `index` becomes the only child of the new node. Returns the new node's index.
+	/// Panics if `index` is invalid.
+	/// Panics if `index` is 0.
+	pub fn wrap(&mut self, index: usize, node: T) -> usize {
+		self.try_wrap(index, node).unwrap_or_else(|e| panic!("{e}"))
+	}
+	pub fn try_wrap(&mut self, index: usize, node: T) -> Result<usize, HeapError> {
+		if index == 0 {
+			return Err(HeapError::RootReparent);
+		}
+		let wrapper = self.try_insert_before(node, index)?;
+		self.relink(index, wrapper, None);
+		Ok(wrapper)
+	}
+	fn parent_for_sibling(&self, sibling: usize) -> Result<usize, HeapError> {
+		if !self.is_valid_idx(sibling) {
+			return Err(HeapError::InvalidIndex(sibling));
+		}
+		self.inner[sibling].parent().ok_or(HeapError::RootSibling)
+	}
+	fn try_insert_after_child(&mut self, node: T, parent: usize, prev: Option<usize>) -> Result<usize, HeapError> {
+		let i = self.next_free();
+		if !fits_raw_index(i) {
+			return Err(HeapError::CapacityOverflow);
+		}
+		self.insert_at(i, node, parent, prev);
+		Ok(i)
+	}
+	fn next_free(&self) -> usize {
+		self.free.first().unwrap_or(self.inner.len())
+	}
+	/// Panics if there is no free index up to `MAX_INDEX`
+	fn insert_unchecked(&mut self, node: T, parent: usize) -> usize {
+		let i = self.next_free();
+		assert!(fits_raw_index(i), "Heap: Error: Ran out of indices");
+		self.insert_at(i, node, parent, self.inner[parent].last_child.get());
+		i
+	}
+	// Places a node in the free slot `index`, or the slot just past the end, right after the
+	// child `prev` of `parent`
+	fn insert_at(&mut self, index: usize, node: T, parent: usize, prev: Option<usize>) {
+		if index == self.inner.len() {
+			self.inner.push(Node::new(node, self.min_generation));
 		} else {
-			self[i] = (node, Some(parent));
+			assert!(self.inner[index].value.is_none(), "Heap: Internal Error: Inserted into an occupied slot.");
+			self.free.remove(index);
+			self.inner[index] = Node::new(node, self.inner[index].generation);
 		}
+		self.link_after(index, parent, prev);
 		self.len += 1;
-		i
+		self.observer.notify(|o| o.on_insert(index));
+	}
+	// Moves the subtree at `index` right after the child `prev` of `parent`, without checking
+	// for cycles
+	fn relink(&mut self, index: usize, parent: usize, prev: Option<usize>) {
+		let old_parent = self.inner[index].parent().expect("Heap: Internal Error: Orphaned node.");
+		self.unlink(index);
+		self.link_after(index, parent, prev);
+		self.observer.notify(|o| o.on_reparent(index, old_parent, parent));
+	}
+	// Places the unattached node at `index` among `parent`'s children, right after the child
+	// `prev`, or first if `prev` is `None`
+	fn link_after(&mut self, index: usize, parent: usize, prev: Option<usize>) {
+		self.pre_order.invalidate();
+		let size = self.inner[index].size;
+		let mut ancestor = Some(parent);
+		while let Some(a) = ancestor {
+			self.inner[a].size += size;
+			ancestor = self.inner[a].parent();
+		}
+		let link = Link::new(Some(index));
+		let next = match prev {
+			Some(p) => core::mem::replace(&mut self.inner[p].next_sibling, link),
+			None => core::mem::replace(&mut self.inner[parent].first_child, link),
+		};
+		match next.get() {
+			Some(n) => self.inner[n].prev_sibling = link,
+			None => self.inner[parent].last_child = link,
+		}
+		self.inner[parent].child_count += 1;
+		let node = &mut self.inner[index];
+		node.parent = Link::new(Some(parent));
+		node.prev_sibling = Link::new(prev);
+		node.next_sibling = next;
+		// A moved subtree keeps its shape, but every node in it may end up at a new depth
+		if self.inner[index].depth != self.inner[parent].depth + 1 {
+			let mut next = Some(index);
+			while let Some(i) = next {
+				let parent = self.inner[i].parent().expect("Heap: Internal Error: Orphaned node.");
+				self.inner[i].depth = self.inner[parent].depth + 1;
+				next = self.pre_order_next(i, index);
+			}
+		}
+	}
+	// Detaches the node at `index` from its parent's children, keeping its own children
+	fn unlink(&mut self, index: usize) {
+		self.pre_order.invalidate();
+		let node = &self.inner[index];
+		let (parent, prev, next) = (node.parent, node.prev_sibling, node.next_sibling);
+		let Some(parent) = parent.get() else {
+			return;
+		};
+		let size = node.size;
+		let mut ancestor = Some(parent);
+		while let Some(a) = ancestor {
+			self.inner[a].size -= size;
+			ancestor = self.inner[a].parent();
+		}
+		match prev.get() {
+			Some(p) => self.inner[p].next_sibling = next,
+			None => self.inner[parent].first_child = next,
+		}
+		match next.get() {
+			Some(n) => self.inner[n].prev_sibling = prev,
+			None => self.inner[parent].last_child = prev,
+		}
+		self.inner[parent].child_count -= 1;
+		let node = &mut self.inner[index];
+		node.parent = Link::NONE;
+		node.prev_sibling = Link::NONE;
+		node.next_sibling = Link::NONE;
 	}
 
+	/// Removes the node at `index` along with all of its descendants.
+	/// Returns the removed values in depth-first pre-order, so `index`'s value comes first
+	/// and every parent precedes its children.
 	/// Panics if `index` is invalid.
 	/// Panics if `index` is 0.
-	pub fn remove(&mut self, index: usize) {
+	pub fn remove(&mut self, index: usize) -> Vec<T> {
 		assert!(index > 0, "Heap: Error: Tried to remove the root node.");
 		assert!(self.is_valid_idx(index), "Heap: Error: Tried to remove an invalid node");
-		let mut should_remove: HashSet<_> = HashSet::from([index]);
-		should_remove.extend(self.descendants_of(index));
-		for node in should_remove {
-			self.free.insert(node);
-			self[node].1 = None;
-			self.len -= 1;
+		self.remove_unchecked(index)
+	}
+	pub fn try_remove(&mut self, index: usize) -> Result<Vec<T>, HeapError> {
+		if index == 0 {
+			return Err(HeapError::RootRemoval);
+		}
+		if !self.is_valid_idx(index) {
+			return Err(HeapError::InvalidIndex(index));
+		}
+		Ok(self.remove_unchecked(index))
+	}
+	fn remove_unchecked(&mut self, index: usize) -> Vec<T> {
+		self.unlink(index);
+		self.subtree(index).into_iter().map(|i| self.free_slot(i)).collect()
+	}
+	/// Removes only the node at `index`, moving its children up into its place among its
+	/// siblings in their existing order. Returns the removed value.
+	/// Panics if `index` is invalid.
+	/// Panics if `index` is 0.
+	pub fn remove_node_keep_children(&mut self, index: usize) -> T {
+		self.try_remove_node_keep_children(index).unwrap_or_else(|e| panic!("{e}"))
+	}
+	pub fn try_remove_node_keep_children(&mut self, index: usize) -> Result<T, HeapError> {
+		if index == 0 {
+			return Err(HeapError::RootRemoval);
+		}
+		if !self.is_valid_idx(index) {
+			return Err(HeapError::InvalidIndex(index));
+		}
+		Ok(self.hoist_children(index))
+	}
+	/// Removes every node deeper than `max_depth`, so `truncate_depth(0)` leaves only the root.
+	/// Returns the number of nodes removed.
+	pub fn truncate_depth(&mut self, max_depth: usize) -> usize {
+		// Nothing is deeper, and `max_depth + 1` below cannot overflow past this point
+		if max_depth >= self.height() {
+			return 0;
+		}
+		let cut: Vec<_> = self.iter_dfs(0)
+			.map(|(i, _)| i)
+			.filter(|&i| self.depth_of(i) == max_depth + 1)
+			.collect();
+		let len = self.len;
+		for i in cut {
+			self.remove_unchecked(i);
+		}
+		len - self.len
+	}
+	/// Removes every node for which `f` returns `false`, along with all of its descendants.
+	/// `f` gets each node's index and value in depth-first pre-order, and is not called for
+	/// the root, which is always kept, or for nodes already removed with an ancestor.
+	pub fn retain<F: FnMut(usize, &T) -> bool>(&mut self, mut f: F) {
+		for i in self.subtree(0).into_iter().skip(1) {
+			if self.inner[i].value.as_ref().is_some_and(|value| !f(i, value)) {
+				self.remove_unchecked(i);
+			}
+		}
+	}
+	/// Removes every node for which `f` returns `false`, moving its children up into its
+	/// place among its siblings. `f` is called for every node but the root, in depth-first
+	/// pre-order.
+	pub fn retain_keep_children<F: FnMut(usize, &T) -> bool>(&mut self, mut f: F) {
+		for i in self.subtree(0).into_iter().skip(1) {
+			if !f(i, &self[i]) {
+				self.hoist_children(i);
+			}
+		}
+	}
+	// Moves the children of `index` into its place among its siblings, keeping their order,
+	// then frees `index`
+	fn hoist_children(&mut self, index: usize) -> T {
+		let parent = self.inner[index].parent().expect("Heap: Internal Error: Orphaned node.");
+		let mut prev = self.inner[index].prev_sibling.get();
+		while let Some(child) = self.inner[index].first_child.get() {
+			self.relink(child, parent, prev);
+			prev = Some(child);
+		}
+		self.unlink(index);
+		self.free_slot(index)
+	}
+	/// Makes the node at `index` the root, removing every node outside of its subtree.
+	/// The node moves to index 0 and all of its descendants keep their indices.
+	/// Returns the removed values in depth-first pre-order, starting with the old root's.
+	/// Panics if `index` is invalid
+	pub fn promote_to_root(&mut self, index: usize) -> Vec<T> {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to promote an invalid node");
+		if index == 0 {
+			return Vec::new();
+		}
+		self.unlink(index);
+		let removed = self.subtree(0).into_iter().map(|i| self.free_slot(i)).collect();
+		self.swap_slots(0, index);
+		self.free.remove(0);
+		self.free.insert(index);
+		self.inner[0].depth = 0;
+		self.shift_depths(0);
+		self.watchers.remap(|i| if i == index { 0 } else { i });
+		self.observer.notify(|o| o.on_reindex(index, 0));
+		removed
+	}
+	/// Makes the node at `index` the root while keeping every node, by reversing the links
+	/// on the path from the old root down to it: each node on the path becomes the last child
+	/// of the node that was its child. The node moves to index 0 and the old root takes its
+	/// index, while every other node keeps its index.
+	/// Panics if `index` is invalid
+	pub fn reroot(&mut self, index: usize) {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to reroot at an invalid node");
+		if index == 0 {
+			return;
+		}
+		let path = self.path_to_root(index);
+		for &i in &path[..path.len() - 1] {
+			self.unlink(i);
+		}
+		self.inner[index].depth = 0;
+		self.shift_depths(index);
+		for (k, pair) in path.windows(2).enumerate() {
+			let (child, parent) = (pair[0], pair[1]);
+			self.link_after(parent, child, self.inner[child].last_child.get());
+			// The old root had no parent, which is reported as its new one
+			let old_parent = path.get(k + 2).copied().unwrap_or(child);
+			self.observer.notify(|o| o.on_reparent(parent, old_parent, child));
 		}
+		self.swap_slots(0, index);
+		self.watchers.remap(|i| match i {
+			0 => index,
+			i if i == index => 0,
+			i => i,
+		});
+		self.observer.notify(|o| {
+			o.on_reindex(index, 0);
+			o.on_reindex(0, index);
+		});
+	}
+	// Exchanges the slots `a` and `b`, along with every link to them. Both get a generation
+	// neither has had, so no `NodeId` follows either node to its new index.
+	fn swap_slots(&mut self, a: usize, b: usize) {
+		self.pre_order.invalidate();
+		let swapped = |i: usize| match i {
+			i if i == a => b,
+			i if i == b => a,
+			i => i,
+		};
+		let mut touched = vec![a, b];
+		for i in [a, b] {
+			let node = &self.inner[i];
+			touched.extend([node.parent, node.prev_sibling, node.next_sibling].iter().filter_map(|link| link.get()));
+			touched.extend(self.children(i));
+		}
+		let mut touched: Vec<_> = touched.into_iter().map(swapped).collect();
+		touched.sort_unstable();
+		touched.dedup();
+		self.inner.swap(a, b);
+		for i in touched {
+			self.inner[i].remap_links(swapped);
+		}
+		let generation = self.inner[a].generation.max(self.inner[b].generation).wrapping_add(1);
+		self.inner[a].generation = generation;
+		self.inner[b].generation = generation;
+	}
+	// Recomputes the depths of the descendants of `index` from its own depth
+	fn shift_depths(&mut self, index: usize) {
+		let mut next = self.pre_order_next(index, index);
+		while let Some(i) = next {
+			let parent = self.inner[i].parent().expect("Heap: Internal Error: Orphaned node.");
+			self.inner[i].depth = self.inner[parent].depth + 1;
+			next = self.pre_order_next(i, index);
+		}
+	}
+	/// Detaches the node at `index` and its descendants into a new heap rooted at that node.
+	/// The detached nodes are numbered in depth-first pre-order.
+	/// Panics if `index` is invalid.
+	/// Panics if `index` is 0.
+	pub fn split_off(&mut self, index: usize) -> Heap<T> {
+		assert!(index > 0, "Heap: Error: Tried to split off the root node.");
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to split off an invalid node");
+		let subtree = self.subtree(index);
+		let mut new_indices = Map::from([(index, 0)]);
+		self.unlink(index);
+		let root = self.free_slot(index);
+		let mut heap = Heap::with_nodes(self.sibling_nodes(subtree.len()), root);
+		for i in subtree.into_iter().skip(1) {
+			let parent = new_indices[&self.inner[i].parent().expect("Heap: Internal Error: Orphaned node.")];
+			let value = self.free_slot(i);
+			new_indices.insert(i, heap.insert(value, parent));
+		}
+		heap
+	}
+	/// Attaches the root of `other` as a child of `parent`, taking ownership of all of its nodes.
+	/// Returns a map from each node's index in `other` to its new index in `self`.
+	/// `WeakNodeId`s taken on `other` follow their nodes to their new indices.
+	/// Panics if `parent` is not the index of a valid node
+	pub fn graft(&mut self, other: Heap<T>, parent: usize) -> IndexMap {
+		assert!(
+			self.is_valid_idx(parent),
+			"Heap: Error: Tried to graft onto an invalid parent"
+		);
+		let mut new_indices = IndexMap::new();
+		self.inner.reserve(other.len());
+		let order = other.subtree(0);
+		let mut nodes: Vec<_> = other.inner.into_iter().map(Some).collect();
+		for i in order {
+			let node = nodes[i].take().expect("Heap: Internal Error: Visited a node twice.");
+			let new_parent = match node.parent() {
+				Some(p) => new_indices[&p],
+				None => parent,
+			};
+			let value = node.value.expect("Heap: Internal Error: Visited a free slot.");
+			new_indices.insert(i, self.insert_unchecked(value, new_parent));
+		}
+		self.watchers.adopt(other.watchers, |i| new_indices[&i]);
+		new_indices
 	}
 
-	pub fn descendants_of(&self, index: usize) -> HashSet<usize> {
-		let mut descendants = HashSet::from([index]);
-		for (idx, node) in self.inner.iter().enumerate().skip(index + 1) {
-			if let Some(i) = node.1 {
-				if descendants.contains(&i) {
-					descendants.insert(idx);
-				}
+	/// Makes `new_parent` the parent of the node at `index`, carrying its descendants along.
+	/// Indices are not changed by the move.
+	/// Panics if `index` or `new_parent` is invalid.
+	/// Panics if `index` is 0.
+	/// Panics if `new_parent` is `index` or one of its descendants.
+	pub fn move_subtree(&mut self, index: usize, new_parent: usize) {
+		if let Err(e) = self.try_move_subtree(index, new_parent) {
+			panic!("{e}");
+		}
+	}
+	pub fn try_move_subtree(&mut self, index: usize, new_parent: usize) -> Result<(), HeapError> {
+		if !self.is_valid_idx(index) {
+			return Err(HeapError::InvalidIndex(index));
+		}
+		if !self.is_valid_idx(new_parent) {
+			return Err(HeapError::InvalidParent(new_parent));
+		}
+		if index == 0 {
+			return Err(HeapError::RootReparent);
+		}
+		if new_parent == index || self.is_above(index, new_parent) {
+			return Err(HeapError::Cycle);
+		}
+		self.relink(index, new_parent, self.inner[new_parent].last_child.get());
+		Ok(())
+	}
+
+	/// Moves the node at `index` to `position` among its parent's children, so that
+	/// `children` yields it after `position` of its siblings. Its descendants come along.
+	/// Panics if `index` is invalid.
+	/// Panics if `index` is 0.
+	/// Panics if `position` is not less than the number of children of `index`'s parent.
+	pub fn move_child(&mut self, index: usize, position: usize) {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to move an invalid node");
+		let parent = self.inner[index].parent().expect("Heap: Error: Tried to move the root node among its siblings");
+		assert!(position < self.child_count(parent), "Heap: Error: Tried to move a child past its last sibling");
+		let prev = match position {
+			0 => None,
+			_ => self.children(parent).filter(|&c| c != index).nth(position - 1),
+		};
+		self.relink(index, parent, prev);
+	}
+
+	/// Exchanges the values of the nodes at `a` and `b`, leaving the tree shape unchanged.
+	/// Panics if `a` or `b` is invalid
+	pub fn swap_values(&mut self, a: usize, b: usize) {
+		assert!(
+			self.is_valid_idx(a) && self.is_valid_idx(b),
+			"Heap: Error: Tried to swap the value of an invalid node"
+		);
+		let value = self.inner[a].value.take();
+		self.inner[a].value = core::mem::replace(&mut self.inner[b].value, value);
+	}
+	/// Exchanges the positions of the subtrees rooted at `a` and `b`, so that each takes the
+	/// other's place among its parent's children. Indices are not changed by the swap.
+	/// Panics if `a` or `b` is invalid.
+	/// Panics if one of `a` and `b` is an ancestor of the other.
+	pub fn swap_subtrees(&mut self, a: usize, b: usize) {
+		if let Err(e) = self.try_swap_subtrees(a, b) {
+			panic!("{e}");
+		}
+	}
+	pub fn try_swap_subtrees(&mut self, a: usize, b: usize) -> Result<(), HeapError> {
+		for index in [a, b] {
+			if !self.is_valid_idx(index) {
+				return Err(HeapError::InvalidIndex(index));
+			}
+		}
+		if a == b {
+			return Ok(());
+		}
+		if self.is_above(a, b) || self.is_above(b, a) {
+			return Err(HeapError::Cycle);
+		}
+		// Neither node is the root, since the root is an ancestor of every other node
+		let parent_of = |heap: &Self, i: usize| heap.inner[i].parent()
+			.expect("Heap: Internal Error: Orphaned node.");
+		let (parent_a, parent_b) = (parent_of(self, a), parent_of(self, b));
+		let (prev_a, prev_b) = (self.inner[a].prev_sibling.get(), self.inner[b].prev_sibling.get());
+		if prev_b == Some(a) {
+			self.unlink(b);
+			self.link_after(b, parent_a, prev_a);
+		} else if prev_a == Some(b) {
+			self.unlink(a);
+			self.link_after(a, parent_b, prev_b);
+		} else {
+			self.unlink(a);
+			self.unlink(b);
+			self.link_after(a, parent_b, prev_b);
+			self.link_after(b, parent_a, prev_a);
+		}
+		self.observer.notify(|o| {
+			o.on_reparent(a, parent_a, parent_b);
+			o.on_reparent(b, parent_b, parent_a);
+		});
+		Ok(())
+	}
+
+	/// Reorders the children of `parent` by comparing their values with `compare`.
+	/// The sort is stable, and only the order of `children` changes, not any index.
+	/// Panics if `parent` is invalid
+	pub fn sort_children_by<F: FnMut(&T, &T) -> Ordering>(&mut self, parent: usize, mut compare: F) {
+		assert!(self.is_valid_idx(parent), "Heap: Error: Tried to sort the children of an invalid node");
+		self.sort_children_unchecked(parent, &mut compare);
+	}
+	/// Sorts the children of every node, as `sort_children_by` does
+	pub fn sort_by<F: FnMut(&T, &T) -> Ordering>(&mut self, mut compare: F) {
+		for i in self.subtree(0) {
+			self.sort_children_unchecked(i, &mut compare);
+		}
+	}
+	fn sort_children_unchecked<F: FnMut(&T, &T) -> Ordering>(&mut self, parent: usize, compare: &mut F) {
+		let mut children: Vec<_> = self.children(parent).collect();
+		if children.len() > 1 {
+			children.sort_by(|&a, &b| compare(&self[a], &self[b]));
+			self.set_child_order(parent, &children);
+		}
+	}
+	// Relinks the children of `parent` in the given order, which must hold each of them once
+	fn set_child_order(&mut self, parent: usize, order: &[usize]) {
+		let mut prev = None;
+		for &child in order {
+			let moved = self.inner[child].prev_sibling.get() != prev;
+			self.inner[child].prev_sibling = Link::new(prev);
+			match prev {
+				Some(p) => self.inner[p].next_sibling = Link::new(Some(child)),
+				None => self.inner[parent].first_child = Link::new(Some(child)),
+			}
+			if moved {
+				self.observer.notify(|o| o.on_reparent(child, parent, parent));
 			}
+			prev = Some(child);
 		}
-		descendants.remove(&index);
-		descendants
+		if let Some(last) = prev {
+			self.inner[last].next_sibling = Link::NONE;
+		}
+		self.inner[parent].last_child = Link::new(prev);
+	}
+
+	// Every node in the subtree rooted at `index`, in depth-first pre-order
+	fn subtree(&self, index: usize) -> Vec<usize> {
+		self.iter_dfs(index).map(|(i, _)| i).collect()
 	}
-	pub fn direct_children_of(&self, index: usize) -> HashSet<usize> {
-		self.inner.iter().enumerate().skip(index + 1).filter_map(|(idx, node)| node.1.and_then(|i|
-			match i == index {
-				true => Some(idx),
-				false => None
+	// The node after `index` in a pre-order traversal of the subtree rooted at `start`
+	fn pre_order_next(&self, mut index: usize, start: usize) -> Option<usize> {
+		if let Some(child) = self.inner[index].first_child.get() {
+			return Some(child);
+		}
+		while index != start {
+			if let Some(sibling) = self.inner[index].next_sibling.get() {
+				return Some(sibling);
 			}
-		)).collect()
+			index = self.inner[index].parent()?;
+		}
+		None
+	}
+	// The node after `index` in a post-order traversal of the subtree rooted at `start`
+	fn post_order_next(&self, index: usize, start: usize) -> Option<usize> {
+		if index == start {
+			return None;
+		}
+		match self.inner[index].next_sibling.get() {
+			Some(sibling) => Some(self.first_leaf(sibling)),
+			None => self.inner[index].parent(),
+		}
+	}
+	// The first node visited by a post-order traversal of the subtree rooted at `index`
+	fn first_leaf(&self, mut index: usize) -> usize {
+		while let Some(child) = self.inner[index].first_child.get() {
+			index = child;
+		}
+		index
+	}
+	// Only the node's own links are cleared, so the caller must unlink it from its parent
+	// and free or reattach its children
+	fn free_slot(&mut self, index: usize) -> T {
+		self.pre_order.invalidate();
+		let node = &mut self.inner[index];
+		node.clear_links();
+		node.generation = node.generation.wrapping_add(1);
+		self.free.insert(index);
+		self.watchers.removed(index);
+		self.observer.notify(|o| o.on_remove(index));
+		self.len -= 1;
+		node.value.take().expect("Heap: Internal Error: Freed an empty slot.")
+	}
+
+	/// Panics if `parent` does not refer to a valid node
+	pub fn insert_id(&mut self, node: T, parent: NodeId) -> NodeId {
+		let parent = self.resolve(parent)
+			.expect("Heap: Error: Tried to insert with invalid parent");
+		let index = self.insert(node, parent);
+		NodeId { index, generation: self.inner[index].generation }
+	}
+
+	/// Returns a generation-checked handle to the node at `index`, or `None` if `index` is invalid
+	pub fn id_of(&self, index: usize) -> Option<NodeId> {
+		match self.is_valid_idx(index) {
+			true => Some(NodeId { index, generation: self.inner[index].generation }),
+			false => None,
+		}
+	}
+	/// Returns the current index of the node `id` refers to.
+	/// Returns `None` if that node has since been removed.
+	pub fn resolve(&self, id: NodeId) -> Option<usize> {
+		match self.is_valid_idx(id.index) && self.inner[id.index].generation == id.generation {
+			true => Some(id.index),
+			false => None,
+		}
+	}
+	pub fn contains_id(&self, id: NodeId) -> bool {
+		self.resolve(id).is_some()
+	}
+	pub fn get_by_id(&self, id: NodeId) -> Option<&T> {
+		self.resolve(id).and_then(|i| self.get(i))
+	}
+	pub fn get_mut_by_id(&mut self, id: NodeId) -> Option<&mut T> {
+		self.resolve(id).and_then(|i| self.get_mut(i))
+	}
+
+	pub fn descendants_of(&self, index: usize) -> IndexSet {
+		self.descendants(index).collect()
+	}
+	/// Lazily yields the indices of every descendant of `index` in depth-first pre-order
+	pub fn descendants(&self, index: usize) -> Descendants<'_, T> {
+		let next = self.inner.get(index).and_then(|node| node.first_child.get());
+		Descendants { heap: self, start: index, next }
+	}
+	/// Lazily yields the indices of every node without children, in the order a depth-first
+	/// traversal reaches them
+	pub fn leaves(&self) -> Leaves<'_, T> {
+		self.leaves_of(0)
+	}
+	/// Like `leaves`, but only within the subtree rooted at `index`, which is its own only
+	/// leaf if it has no children. Yields nothing if `index` is invalid.
+	pub fn leaves_of(&self, index: usize) -> Leaves<'_, T> {
+		let next = self.is_valid_idx(index).then(|| self.first_leaf(index));
+		Leaves { heap: self, start: index, next }
+	}
+	/// Lazily yields every `(parent, child)` pair, so a heap of `len` nodes has `len - 1` edges
+	pub fn edges(&self) -> Edges<'_, T> {
+		Edges { descendants: self.descendants(0) }
+	}
+	pub fn direct_children_of(&self, index: usize) -> IndexSet {
+		self.children(index).collect()
+	}
+	/// Lazily yields the indices of the direct children of `index` in sibling order, which is
+	/// the order they were attached in unless they were placed with `insert_before`,
+	/// `insert_after` or `move_child`, or sorted
+	pub fn children(&self, index: usize) -> Children<'_, T> {
+		let (front, back) = match self.inner.get(index) {
+			Some(node) => (node.first_child.get(), node.last_child.get()),
+			None => (None, None),
+		};
+		Children { heap: self, front, back }
+	}
+
+	/// Panics if `index` is invalid
+	pub fn is_leaf(&self, index: usize) -> bool {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to inspect an invalid node");
+		self.inner[index].first_child.get().is_none()
+	}
+	/// Takes constant time.
+	/// Panics if `index` is invalid
+	#[allow(clippy::unnecessary_cast)]
+	pub fn child_count(&self, index: usize) -> usize {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to inspect an invalid node");
+		self.inner[index].child_count as usize
+	}
+	/// Returns the number of nodes in the subtree rooted at `index`, including `index` itself.
+	/// Panics if `index` is invalid
+	#[allow(clippy::unnecessary_cast)]
+	pub fn subtree_size(&self, index: usize) -> usize {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to get the size of an invalid node");
+		self.inner[index].size as usize
+	}
+
+	/// Returns the number of edges between `index` and the root, so the root has depth 0.
+	/// Panics if `index` is invalid
+	#[allow(clippy::unnecessary_cast)]
+	pub fn depth_of(&self, index: usize) -> usize {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to get the depth of an invalid node");
+		self.inner[index].depth as usize
+	}
+
+	/// Returns the depth of the deepest node, so a heap holding only its root has height 0
+	pub fn height(&self) -> usize {
+		self.height_of(0)
+	}
+	/// Returns the number of edges on the longest downward path from `index` to a leaf.
+	/// Takes time proportional to the size of the subtree.
+	/// Panics if `index` is invalid
+	pub fn height_of(&self, index: usize) -> usize {
+		let depth = self.depth_of(index);
+		self.descendants(index).map(|i| self.depth_of(i) - depth).max().unwrap_or(0)
+	}
+
+	/// Lazily yields the indices of the other children of `index`'s parent, in the same order
+	/// as `children`. The root has no siblings.
+	pub fn siblings_of(&self, index: usize) -> Siblings<'_, T> {
+		let parent = self.inner.get(index).and_then(|node| node.parent());
+		let children = match parent {
+			Some(p) => self.children(p),
+			None => Children { heap: self, front: None, back: None },
+		};
+		Siblings { children, index }
+	}
+
+	/// Returns `None` for the root node.
+	/// Panics if `index` is invalid
+	pub fn parent_of(&self, index: usize) -> Option<usize> {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to get the parent of an invalid node");
+		self.inner[index].parent()
+	}
+	/// Lazily yields the indices of the ancestors of `index`, from its parent up to the root
+	pub fn ancestors(&self, index: usize) -> Ancestors<'_, T> {
+		Ancestors { heap: self, next: self.inner.get(index).and_then(|node| node.parent()) }
+	}
+
+	/// Returns whether `a` is a proper ancestor of `b`, so a node is not its own ancestor.
+	/// Takes constant time by comparing pre-order intervals, which the first check after the
+	/// tree's shape changes builds in O(n).
+	/// Panics if `a` or `b` is invalid
+	#[allow(clippy::unnecessary_cast)]
+	pub fn is_ancestor_of(&self, a: usize, b: usize) -> bool {
+		assert!(self.is_valid_idx(a) && self.is_valid_idx(b), "Heap: Error: Tried to inspect an invalid node");
+		let numbers = self.pre_order_numbers();
+		numbers[a] < numbers[b] && numbers[b] < numbers[a] + self.inner[a].size as usize
+	}
+	/// Returns whether `a` is a proper descendant of `b`.
+	/// Panics if `a` or `b` is invalid
+	pub fn is_descendant_of(&self, a: usize, b: usize) -> bool {
+		self.is_ancestor_of(b, a)
+	}
+	// Like `is_ancestor_of`, but walks only the levels between the two nodes using the cached
+	// depths. Edits check with this, so they never rebuild the pre-order intervals.
+	fn is_above(&self, a: usize, b: usize) -> bool {
+		let (depth_a, depth_b) = (self.depth_of(a), self.depth_of(b));
+		depth_a < depth_b && self.ancestors(b).nth(depth_b - depth_a - 1) == Some(a)
+	}
+
+	/// Returns the indices from `index` up to the root, including both ends.
+	/// Panics if `index` is invalid
+	pub fn path_to_root(&self, index: usize) -> Vec<usize> {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to find a path from an invalid node");
+		core::iter::once(index).chain(self.ancestors(index)).collect()
+	}
+	/// Returns the indices on the path from `a` up to the lowest node that has both `a` and
+	/// `b` below it, and then down to `b`, including both ends.
+	/// Panics if `a` or `b` is invalid
+	pub fn path_between(&self, a: usize, b: usize) -> Vec<usize> {
+		assert!(
+			self.is_valid_idx(a) && self.is_valid_idx(b),
+			"Heap: Error: Tried to find a path between invalid nodes"
+		);
+		let lca = self.lowest_common_ancestor(a, b);
+		let up_to_lca = |from| core::iter::once(from).chain(self.ancestors(from)).take_while(|&i| i != lca);
+		let mut path: Vec<_> = up_to_lca(a).collect();
+		path.push(lca);
+		let down: Vec<_> = up_to_lca(b).collect();
+		path.extend(down.into_iter().rev());
+		path
+	}
+	/// Returns the deepest node that has both `a` and `b` in its subtree. A node counts as
+	/// being in its own subtree, so this is `a` when `b` descends from `a`.
+	/// Takes time proportional to the depth of the nodes.
+	/// Panics if `a` or `b` is invalid
+	pub fn lowest_common_ancestor(&self, mut a: usize, mut b: usize) -> usize {
+		assert!(
+			self.is_valid_idx(a) && self.is_valid_idx(b),
+			"Heap: Error: Tried to find the common ancestor of invalid nodes"
+		);
+		let parent = |i: usize| self.inner[i].parent().expect("Heap: Internal Error: Orphaned node.");
+		while self.depth_of(a) > self.depth_of(b) {
+			a = parent(a);
+		}
+		while self.depth_of(b) > self.depth_of(a) {
+			b = parent(b);
+		}
+		while a != b {
+			(a, b) = (parent(a), parent(b));
+		}
+		a
+	}
+
+	/// Iterates over the subtree rooted at `start` in depth-first pre-order,
+	/// visiting siblings in the same order as `children`.
+	/// Panics if `start` is invalid
+	pub fn iter_dfs(&self, start: usize) -> Dfs<'_, T> {
+		assert!(self.is_valid_idx(start), "Heap: Error: Tried to traverse from an invalid node");
+		Dfs { heap: self, start, next: Some(start) }
+	}
+
+	/// Iterates over the subtree rooted at `start` in depth-first post-order,
+	/// so every node is visited after all of its descendants.
+	/// Panics if `start` is invalid
+	pub fn iter_post_order(&self, start: usize) -> PostOrder<'_, T> {
+		assert!(self.is_valid_idx(start), "Heap: Error: Tried to traverse from an invalid node");
+		PostOrder { heap: self, start, next: Some(self.first_leaf(start)) }
+	}
+
+	/// Iterates over the subtree rooted at `start` level by level, yielding each node's depth
+	/// relative to `start` along with its index and value.
+	/// Panics if `start` is invalid
+	pub fn iter_bfs(&self, start: usize) -> Bfs<'_, T> {
+		assert!(self.is_valid_idx(start), "Heap: Error: Tried to traverse from an invalid node");
+		Bfs { heap: self, queue: VecDeque::from([(0, start)]) }
+	}
+
+	/// Iterates over the heap level by level, yielding the indices at each depth in turn
+	pub fn levels(&self) -> Levels<'_, T> {
+		self.levels_of(0)
+	}
+	/// Like `levels`, but only within the subtree rooted at `start`, so the first level holds
+	/// only `start`.
+	/// Panics if `start` is invalid
+	pub fn levels_of(&self, start: usize) -> Levels<'_, T> {
+		assert!(self.is_valid_idx(start), "Heap: Error: Tried to traverse from an invalid node");
+		Levels { heap: self, level: vec![start] }
+	}
+
+	/// Returns the first node whose value matches `predicate` in depth-first pre-order from
+	/// the root
+	pub fn find<P: FnMut(&T) -> bool>(&self, predicate: P) -> Option<usize> {
+		self.find_all(predicate).next()
+	}
+	/// Lazily yields every node whose value matches `predicate` in depth-first pre-order from
+	/// the root
+	pub fn find_all<P: FnMut(&T) -> bool>(&self, predicate: P) -> FindAll<'_, T, P> {
+		FindAll { dfs: self.iter_dfs(0), predicate }
 	}
 
+	// A heap always contains its root, so it can never be empty
+	#[allow(clippy::len_without_is_empty)]
 	pub fn len(&self) -> usize {
 		self.len
 	}
 
 	pub fn is_valid_idx(&self, index: usize) -> bool {
-		return index == 0 || self[index].1.is_some()
+		self.inner.get(index).is_some_and(|node| node.value.is_some())
+	}
+
+	/// Returns `None` if `index` is invalid
+	pub fn get(&self, index: usize) -> Option<&T> {
+		self.inner.get(index).and_then(|node| node.value.as_ref())
+	}
+	/// Returns `None` if `index` is invalid
+	pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+		self.inner.get_mut(index).and_then(|node| node.value.as_mut())
+	}
+
+	/// Returns mutable references to the values of several distinct nodes at once, in the
+	/// order of `indices`
+	pub fn get_disjoint_mut<const N: usize>(&mut self, indices: [usize; N]) -> Result<[&mut T; N], HeapError> {
+		for (n, &index) in indices.iter().enumerate() {
+			if !self.is_valid_idx(index) {
+				return Err(HeapError::InvalidIndex(index));
+			}
+			if indices[..n].contains(&index) {
+				return Err(HeapError::DuplicateIndex(index));
+			}
+		}
+		let nodes = self.inner.get_disjoint_mut(indices)
+			.expect("Heap: Internal Error: Checked indices were rejected.");
+		Ok(nodes.map(|node| node.value.as_mut().expect("Heap: Internal Error: Checked index was free.")))
+	}
+
+	/// Puts `value` in the node at `index` and returns the value it held before.
+	/// Panics if `index` is invalid
+	pub fn replace(&mut self, index: usize, value: T) -> T {
+		let old = self.get_mut(index).expect("Heap: Error: Tried to replace the value of an invalid node");
+		core::mem::replace(old, value)
+	}
+
+	pub fn root(&self) -> &T {
+		&self[0]
+	}
+	pub fn root_mut(&mut self) -> &mut T {
+		&mut self[0]
+	}
+	/// Returns the previous root value
+	pub fn set_root_value(&mut self, value: T) -> T {
+		self.replace(0, value)
+	}
+
+	/// Removes every node except the root, dropping their values right away, while keeping
+	/// the allocated capacity for the nodes inserted next
+	pub fn clear(&mut self) {
+		let generation = self.next_min_generation();
+		for i in 1..self.inner.len() {
+			if self.inner[i].value.is_some() {
+				self.watchers.removed(i);
+				self.observer.notify(|o| o.on_remove(i));
+			}
+		}
+		self.inner.truncate(1);
+		self.pre_order.invalidate();
+		self.free.clear();
+		// Slots pushed from now on must not match the `NodeId`s of the removed nodes
+		self.min_generation = generation;
+		let root = &mut self.inner[0];
+		root.clear_links();
+		root.size = 1;
+		self.len = 1;
+	}
+	/// Like `clear`, but also gives the root a new value.
+	/// Returns the root's previous value.
+	pub fn reset(&mut self, root: T) -> T {
+		self.clear();
+		self.set_root_value(root)
+	}
+
+	/// The number of freed slots still held by the heap, waiting to be reused
+	pub fn free_slots(&self) -> usize {
+		self.inner.len() - self.len
+	}
+	/// The length of the longest run of consecutive occupied slots
+	pub fn largest_live_run(&self) -> usize {
+		self.inner.split(|node| node.value.is_none()).map(<[_]>::len).max().unwrap_or(0)
+	}
+	/// The fraction of slots that are free, from 0 for a fully packed heap towards 1.
+	/// `compact` brings this back to 0.
+	pub fn fragmentation(&self) -> f64 {
+		self.free_slots() as f64 / self.inner.len() as f64
+	}
+
+	/// Moves every node into the lowest slots, keeping their relative order, and releases the
+	/// memory of the unused slots.
+	/// Returns a table from each old index to its new one, with `None` for slots that were free.
+	/// Invalidates every existing `NodeId`.
+	pub fn compact(&mut self) -> Vec<Option<usize>> {
+		let mut new_indices = vec![None; self.inner.len()];
+		let mut next = 0;
+		for (i, node) in self.inner.iter().enumerate() {
+			if node.value.is_some() {
+				new_indices[i] = Some(next);
+				next += 1;
+			}
+		}
+		let generation = self.next_min_generation();
+		self.inner.retain(|node| node.value.is_some());
+		for node in self.inner.iter_mut() {
+			node.remap_links(|i| new_indices[i].expect("Heap: Internal Error: Linked to a free slot."));
+			node.generation = generation;
+		}
+		self.inner.shrink_to_fit();
+		self.pre_order.invalidate();
+		self.free.clear();
+		self.min_generation = generation;
+		self.watchers.remap(|i| new_indices[i].expect("Heap: Internal Error: Watched a free slot."));
+		self.observer.notify(|o| {
+			for (old, &new) in new_indices.iter().enumerate() {
+				match new {
+					Some(new) if new != old => o.on_reindex(old, new),
+					_ => (),
+				}
+			}
+		});
+		new_indices
+	}
+
+	/// Rearranges the nodes so that their indices follow a depth-first pre-order traversal,
+	/// making every subtree a contiguous run of slots, and releases the memory of unused slots.
+	/// `remap` is called with the old and new index of every node.
+	/// Invalidates every existing `NodeId`.
+	pub fn reorder_dfs<F: FnMut(usize, usize)>(&mut self, mut remap: F) {
+		let order = self.subtree(0);
+		let mut new_indices = vec![0; self.inner.len()];
+		for (new, &old) in order.iter().enumerate() {
+			new_indices[old] = new;
+		}
+		let generation = self.next_min_generation();
+		let nodes = self.sibling_nodes(order.len());
+		let mut old_nodes: Vec<_> = core::mem::replace(&mut self.inner, nodes)
+			.into_iter().map(Some).collect();
+		for (new, old) in order.into_iter().enumerate() {
+			let mut node = old_nodes[old].take().expect("Heap: Internal Error: Visited a node twice.");
+			node.remap_links(|i| new_indices[i]);
+			node.generation = generation;
+			self.inner.push(node);
+			remap(old, new);
+			if old != new {
+				self.observer.notify(|o| o.on_reindex(old, new));
+			}
+		}
+		self.pre_order.invalidate();
+		self.free.clear();
+		self.min_generation = generation;
+		self.watchers.remap(|i| new_indices[i]);
+	}
+	// A generation newer than any slot has had, so that no existing `NodeId` can match it
+	fn next_min_generation(&self) -> u32 {
+		self.inner.iter().map(|node| node.generation)
+			.fold(self.min_generation, u32::max)
+			.wrapping_add(1)
+	}
+
+	/// The number of bytes each slot occupies. Besides the value itself, every slot stores
+	/// five index-sized links to its parent, first and last child, and previous and next
+	/// sibling, which are niche-optimized so that missing links take no extra space, an
+	/// index-sized subtree size and depth, and a 32-bit generation counter.
+	pub const SLOT_SIZE: usize = size_of::<Node<T>>();
+
+	/// An estimate of the bytes allocated by the heap itself, not counting any memory owned by
+	/// the values
+	pub fn memory_usage(&self) -> usize {
+		self.capacity() * Self::SLOT_SIZE + self.free.memory_usage()
 	}
 
 	// Inner exposures
 	pub fn capacity(&self) -> usize {
 		self.inner.capacity()
 	}
-	pub fn iter(&self) -> impl Iterator<Item = &T> {
-		self.inner.iter().enumerate().filter(|(idx, _)| self.is_valid_idx(*idx)).map(|x| &x.1.0)
+	pub fn iter(&self) -> Iter<'_, T> {
+		Iter { inner: self.inner.iter() }
+	}
+	pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+		IterMut { inner: self.inner.iter_mut() }
 	}
 	pub fn shrink_to(&mut self, min_capacity: usize) {
 		self.inner.shrink_to(min_capacity)
@@ -117,16 +1408,60 @@ impl<T: std::fmt::Debug> Heap<T> {
 	}
 }
 
+impl<T: PartialEq> Heap<T> {
+	pub fn contains(&self, value: &T) -> bool {
+		self.iter().any(|v| v == value)
+	}
+	/// Returns the first node holding `value` in depth-first pre-order from the root, so when
+	/// there are duplicates, ancestors come before descendants and earlier siblings before
+	/// later ones
+	pub fn position_of(&self, value: &T) -> Option<usize> {
+		self.find(|v| v == value)
+	}
+}
+
+/// Heaps are equal when they hold equal values in the same tree shape, with siblings in the
+/// same order. The slots the nodes occupy are not compared.
+impl<T: PartialEq> PartialEq for Heap<T> {
+	fn eq(&self, other: &Self) -> bool {
+		if self.len() != other.len() {
+			return false;
+		}
+		// A pre-order listing of values and child counts determines the tree uniquely
+		self.iter_dfs(0).zip(other.iter_dfs(0)).all(|((i, a), (j, b))|
+			a == b && self.child_count(i) == other.child_count(j)
+		)
+	}
+}
+impl<T: Eq> Eq for Heap<T> {}
+
+/// Hashes the same pre-order listing that equality compares
+impl<T: Hash> Hash for Heap<T> {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.len().hash(state);
+		for (i, value) in self.iter_dfs(0) {
+			value.hash(state);
+			self.child_count(i).hash(state);
+		}
+	}
+}
+
+/// Yields the node's value.
+/// Panics if `index` is invalid
 impl<T> Index<usize> for Heap<T> {
-	type Output = (T, Option<usize>);
+	type Output = T;
 
 	fn index(&self, index: usize) -> &Self::Output {
-		&self.inner[index]
+		self.inner.get(index).and_then(|node| node.value.as_ref())
+			.expect("Heap: Error: Tried to index an invalid node")
 	}
 }
+/// Yields the node's value.
+/// Panics if `index` is invalid
 impl<T> IndexMut<usize> for Heap<T> {
 	fn index_mut(&mut self, index: usize) -> &mut Self::Output {
-		&mut self.inner[index]
+		self.inner.get_mut(index).and_then(|node| node.value.as_mut())
+			.expect("Heap: Error: Tried to index an invalid node")
 	}
 }
 
@@ -159,6 +1494,351 @@ mod tests {
 		assert_eq!(heap.len(), 5)
 	}
 	#[test]
+	fn remove_returns_values_test() {
+		let mut heap = make_test_heap();
+		let removed = heap.remove(1);
+		assert_eq!(removed, vec!["first child", "first grandchild", "great grandchild", "second grandchild"]);
+		assert_eq!(heap.remove(3), vec!["third child"]);
+		assert_eq!(heap.len(), 4);
+	}
+	#[test]
+	fn remove_node_keep_children_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.remove_node_keep_children(2), "second child");
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![1, 6, 7, 3]);
+		assert_eq!(heap.remove_node_keep_children(1), "first child");
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![4, 5, 6, 7, 3]);
+		assert_eq!(heap.parent_of(8), Some(4));
+		assert_eq!(heap.depth_of(8), 2);
+		assert_eq!(heap.remove_node_keep_children(3), "third child");
+		assert_eq!(heap.children(0).next_back(), Some(7));
+		assert_eq!((heap.len(), heap.subtree_size(0)), (6, 6));
+		assert_eq!(heap.try_remove_node_keep_children(0), Err(HeapError::RootRemoval));
+		assert_eq!(heap.try_remove_node_keep_children(3), Err(HeapError::InvalidIndex(3)));
+	}
+	#[test]
+	fn truncate_depth_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.truncate_depth(3), 0);
+		assert_eq!(heap.truncate_depth(usize::MAX), 0);
+		assert_eq!(heap.len(), 9);
+		assert_eq!(heap.truncate_depth(1), 5);
+		assert_eq!(heap.height(), 1);
+		assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec!["root", "first child", "second child", "third child"]);
+		assert_eq!(heap.subtree_size(0), 4);
+		assert_eq!(heap.truncate_depth(0), 3);
+		assert_eq!(heap.len(), 1);
+	}
+	#[test]
+	fn retain_test() {
+		let mut heap = make_test_heap();
+		let mut visited = Vec::new();
+		heap.retain(|i, value| {
+			visited.push(i);
+			!value.starts_with("first")
+		});
+		assert_eq!(visited, vec![1, 2, 6, 7, 3]);
+		assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec![
+			"root", "second child", "third child", "third grandchild", "fourth grandchild",
+		]);
+		let mut heap = make_test_heap();
+		heap.retain_keep_children(|_, value| !value.ends_with(" child"));
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![4, 5, 6, 7]);
+		assert_eq!((heap.depth_of(4), heap.depth_of(8)), (1, 2));
+		assert_eq!((heap.len(), heap.subtree_size(0), heap.subtree_size(4)), (6, 6, 2));
+	}
+	#[test]
+	fn promote_to_root_test() {
+		let mut heap = make_test_heap();
+		let stale = heap.id_of(1).unwrap();
+		assert_eq!(heap.promote_to_root(1), vec!["root", "second child", "third grandchild", "fourth grandchild", "third child"]);
+		assert_eq!(heap[0], "first child");
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![4, 5]);
+		assert_eq!((heap.parent_of(4), heap.parent_of(0)), (Some(0), None));
+		assert_eq!((heap.depth_of(4), heap.depth_of(8), heap.height()), (1, 2, 2));
+		assert_eq!((heap.len(), heap.subtree_size(0), heap.free_slots()), (4, 4, 5));
+		assert!(!heap.contains_id(stale));
+		assert_eq!(heap.insert("new", 0), 1);
+		assert!(heap.promote_to_root(0).is_empty());
+	}
+	#[test]
+	fn reroot_test() {
+		let mut heap = make_test_heap();
+		heap.reroot(4);
+		assert_eq!(heap[0], "first grandchild");
+		assert_eq!(heap[4], "root");
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![8, 1]);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![5, 4]);
+		assert_eq!(heap.children(4).collect::<Vec<_>>(), vec![2, 3]);
+		assert_eq!(heap.depth_of(7), 4);
+		assert_eq!((heap.height(), heap.len(), heap.subtree_size(0), heap.subtree_size(4)), (4, 9, 9, 5));
+		for (i, _) in heap.iter_dfs(0) {
+			assert_eq!(heap.subtree_size(i), heap.iter_dfs(i).count());
+		}
+		heap.reroot(4);
+		assert_eq!(heap[0], "root");
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![2, 3, 1]);
+	}
+	#[test]
+	fn split_off_test() {
+		let mut heap = make_test_heap();
+		let split = heap.split_off(1);
+		assert_eq!(heap.len(), 5);
+		assert_eq!(heap.get(4), None);
+		assert_eq!(split.len(), 4);
+		assert_eq!(split[0], "first child");
+		assert_eq!(split.direct_children_of(0).len(), 2);
+		let great = split.iter().position(|x| *x == "great grandchild").unwrap();
+		let grand = split.iter().position(|x| *x == "first grandchild").unwrap();
+		assert_eq!(split.direct_children_of(grand), IndexSet::from([great]));
+	}
+	#[test]
+	fn graft_test() {
+		let mut heap = make_test_heap();
+		let mut split = heap.split_off(1);
+		split.remove(3);
+		let indices = heap.graft(split, 3);
+		assert_eq!(heap.len(), 8);
+		assert_eq!(indices.len(), 3);
+		assert_eq!(heap[indices[&0]], "first child");
+		assert_eq!(heap.direct_children_of(3), IndexSet::from([indices[&0]]));
+		assert_eq!(heap.direct_children_of(indices[&0]), IndexSet::from([indices[&1]]));
+		assert_eq!(heap.direct_children_of(indices[&1]), IndexSet::from([indices[&2]]));
+		assert_eq!(heap[indices[&2]], "great grandchild");
+
+		let mut other = Heap::new("grafted");
+		let leaf = other.insert("leaf", 0);
+		let weak = other.weak_id(leaf);
+		let indices = heap.graft(other, 0);
+		assert_eq!(weak.index(), Some(indices[&leaf]));
+		assert_eq!(heap[weak.index().unwrap()], "leaf");
+	}
+	#[test]
+	fn move_subtree_test() {
+		let mut heap = make_test_heap();
+		heap.move_subtree(1, 7);
+		assert_eq!(heap.direct_children_of(0), IndexSet::from([2, 3]));
+		assert_eq!(heap.direct_children_of(7), IndexSet::from([1]));
+		assert_eq!(heap.descendants_of(2), IndexSet::from([6, 7, 1, 4, 5, 8]));
+		assert_eq!(heap.try_move_subtree(2, 8), Err(HeapError::Cycle));
+		assert_eq!(heap.try_move_subtree(2, 2), Err(HeapError::Cycle));
+		assert_eq!(heap.try_move_subtree(0, 3), Err(HeapError::RootReparent));
+		assert_eq!(heap.try_move_subtree(4, 42), Err(HeapError::InvalidParent(42)));
+		assert_eq!(heap.remove(2), vec![
+			"second child", "third grandchild", "fourth grandchild", "first child",
+			"first grandchild", "great grandchild", "second grandchild",
+		]);
+		assert_eq!(heap.len(), 2);
+	}
+	#[test]
+	fn sibling_order_test() {
+		let mut heap = make_test_heap();
+		heap.move_subtree(6, 1);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![4, 5, 6]);
+		heap.move_subtree(4, 1);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![5, 6, 4]);
+		assert_eq!(heap.children(1).rev().collect::<Vec<_>>(), vec![4, 6, 5]);
+		heap.remove(6);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![5, 4]);
+		assert_eq!(heap.children(2).collect::<Vec<_>>(), vec![7]);
+		let new = heap.insert("new", 1);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![5, 4, new]);
+		assert_eq!(heap.children(new).next(), None);
+		heap.compact();
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![1, 2, 3]);
+		assert_eq!(heap[heap.children(1).next_back().unwrap()], "new");
+	}
+	#[test]
+	fn subtree_size_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.subtree_size(0), 9);
+		assert_eq!(heap.subtree_size(1), 4);
+		assert_eq!(heap.subtree_size(8), 1);
+		heap.move_subtree(1, 7);
+		assert_eq!(heap.subtree_size(0), 9);
+		assert_eq!(heap.subtree_size(2), 7);
+		assert_eq!(heap.subtree_size(7), 5);
+		heap.remove(4);
+		assert_eq!(heap.subtree_size(2), 5);
+		let split = heap.split_off(7);
+		assert_eq!(split.subtree_size(0), 3);
+		assert_eq!(heap.subtree_size(0), 4);
+		let new = heap.insert("new", 6);
+		assert_eq!(heap.subtree_size(new), 1);
+		assert_eq!(heap.subtree_size(2), 3);
+		heap.compact();
+		assert!((0..heap.len()).all(|i| heap.subtree_size(i) == heap.iter_dfs(i).count()));
+	}
+	#[test]
+	fn depth_of_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.depth_of(0), 0);
+		assert_eq!(heap.depth_of(3), 1);
+		assert_eq!(heap.depth_of(8), 3);
+		heap.move_subtree(1, 7);
+		assert_eq!(heap.depth_of(1), 3);
+		assert_eq!(heap.depth_of(8), 5);
+		heap.move_subtree(4, 0);
+		assert_eq!(heap.depth_of(4), 1);
+		assert_eq!(heap.depth_of(8), 2);
+		let split = heap.split_off(2);
+		assert_eq!(split.depth_of(0), 0);
+		assert!((0..split.len()).all(|i| split.depth_of(i) == split.ancestors(i).count()));
+		let new = heap.insert("new", 8);
+		assert_eq!(heap.depth_of(new), 3);
+	}
+	#[test]
+	fn leaf_test() {
+		let mut heap = make_test_heap();
+		assert!(!heap.is_leaf(0));
+		assert!(heap.is_leaf(3));
+		assert_eq!(heap.child_count(0), 3);
+		assert_eq!(heap.child_count(4), 1);
+		heap.remove(8);
+		assert!(heap.is_leaf(4));
+		assert_eq!(heap.child_count(4), 0);
+
+		// The stored counts follow every way of relinking children
+		let counted = |heap: &Heap<&str>| heap.iter_dfs(0).all(|(i, _)| heap.child_count(i) == heap.children(i).count());
+		heap.move_subtree(2, 4);
+		heap.wrap(1, "wrapper");
+		heap.insert_before("before", 3);
+		assert!(counted(&heap));
+		heap.remove_node_keep_children(1);
+		heap.graft(make_test_heap(), 5);
+		heap.compact();
+		assert!(counted(&heap));
+		let split = heap.split_off(heap.find(|&v| v == "wrapper").unwrap());
+		assert!(counted(&heap) && counted(&split));
+		heap.clear();
+		assert_eq!(heap.child_count(0), 0);
+	}
+	#[test]
+	fn leaves_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.leaves().collect::<Vec<_>>(), vec![8, 5, 6, 7, 3]);
+		assert_eq!(heap.leaves_of(1).collect::<Vec<_>>(), vec![8, 5]);
+		assert_eq!(heap.leaves_of(8).collect::<Vec<_>>(), vec![8]);
+		heap.remove(2);
+		assert_eq!(heap.leaves_of(2).count(), 0);
+		assert_eq!(heap.leaves().collect::<Vec<_>>(), vec![8, 5, 3]);
+		assert!(heap.leaves().all(|i| heap.is_leaf(i)));
+		assert_eq!(Heap::new(()).leaves().collect::<Vec<_>>(), vec![0]);
+	}
+	#[test]
+	fn levels_test() {
+		let heap = make_test_heap();
+		assert_eq!(heap.levels().collect::<Vec<_>>(), vec![vec![0], vec![1, 2, 3], vec![4, 5, 6, 7], vec![8]]);
+		assert_eq!(heap.levels_of(2).collect::<Vec<_>>(), vec![vec![2], vec![6, 7]]);
+		assert_eq!(heap.levels().count(), heap.height() + 1);
+	}
+	#[test]
+	fn edges_test() {
+		let mut heap = make_test_heap();
+		heap.remove(2);
+		assert_eq!(heap.edges().collect::<Vec<_>>(), vec![(0, 1), (1, 4), (4, 8), (1, 5), (0, 3)]);
+		assert_eq!(heap.edges().count(), heap.len() - 1);
+		assert_eq!(Heap::new(()).edges().next(), None);
+	}
+	#[test]
+	fn height_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.height(), 3);
+		assert_eq!(heap.height_of(1), 2);
+		assert_eq!(heap.height_of(2), 1);
+		assert_eq!(heap.height_of(8), 0);
+		heap.move_subtree(1, 7);
+		assert_eq!(heap.height(), 5);
+		heap.remove(4);
+		assert_eq!(heap.height_of(2), 3);
+		assert_eq!(Heap::new(()).height(), 0);
+	}
+	#[test]
+	fn swap_test() {
+		let mut heap = make_test_heap();
+		heap.swap_values(1, 8);
+		assert_eq!((heap[1], heap[8]), ("great grandchild", "first child"));
+		assert_eq!(heap.descendants_of(1), IndexSet::from([4, 5, 8]));
+		heap.swap_values(1, 8);
+		heap.swap_subtrees(4, 6);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![6, 5]);
+		assert_eq!(heap.children(2).collect::<Vec<_>>(), vec![4, 7]);
+		assert_eq!((heap.subtree_size(1), heap.subtree_size(2)), (3, 4));
+		assert_eq!(heap.depth_of(8), 3);
+		heap.swap_subtrees(2, 3);
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![1, 3, 2]);
+		heap.swap_subtrees(3, 1);
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![3, 1, 2]);
+		heap.swap_subtrees(6, 8);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![8, 5]);
+		assert_eq!(heap.children(4).collect::<Vec<_>>(), vec![6]);
+		assert_eq!((heap.depth_of(8), heap.depth_of(6)), (2, 3));
+		assert_eq!(heap.try_swap_subtrees(2, 4), Err(HeapError::Cycle));
+		assert_eq!(heap.try_swap_subtrees(0, 5), Err(HeapError::Cycle));
+		assert_eq!(heap.try_swap_subtrees(5, 42), Err(HeapError::InvalidIndex(42)));
+	}
+	#[test]
+	fn insert_children_test() {
+		let mut heap = make_test_heap();
+		heap.remove(2);
+		assert_eq!(heap.insert_children(4, ["x", "y", "z"]), 9..12);
+		assert_eq!(heap.children(4).collect::<Vec<_>>(), vec![8, 9, 10, 11]);
+		assert_eq!((heap.depth_of(10), heap.subtree_size(1)), (3, 7));
+		assert_eq!(heap.insert_children(0, []), 12..12);
+		assert_eq!(heap.insert("reuses", 0), 2);
+	}
+	#[test]
+	fn sibling_insert_test() {
+		let mut heap = tree!("root" => ["b", "d" => ["d1"]]);
+		let a = heap.insert_before("a", 1);
+		let c = heap.insert_after("c", 1);
+		let e = heap.insert_after("e", 2);
+		assert_eq!(heap.children(0).map(|i| heap[i]).collect::<Vec<_>>(), vec!["a", "b", "c", "d", "e"]);
+		assert_eq!(heap.children(0).next_back(), Some(e));
+		assert_eq!(heap.depth_of(c), 1);
+		assert_eq!(heap.subtree_size(0), 7);
+		heap.move_child(a, 4);
+		heap.move_child(2, 0);
+		assert_eq!(heap.children(0).map(|i| heap[i]).collect::<Vec<_>>(), vec!["d", "b", "c", "e", "a"]);
+		heap.move_child(c, 2);
+		assert_eq!(heap.children(0).map(|i| heap[i]).collect::<Vec<_>>(), vec!["d", "b", "c", "e", "a"]);
+		assert_eq!(heap.try_insert_before("x", 0), Err(HeapError::RootSibling));
+		assert_eq!(heap.try_insert_after("x", 42), Err(HeapError::InvalidIndex(42)));
+		assert_eq!(heap.subtree_size(0), 7);
+	}
+	#[test]
+	fn wrap_test() {
+		let mut heap = make_test_heap();
+		let group = heap.wrap(2, "group");
+		assert_eq!(group, 9);
+		assert_eq!(heap.children(0).collect::<Vec<_>>(), vec![1, group, 3]);
+		assert_eq!(heap.children(group).collect::<Vec<_>>(), vec![2]);
+		assert_eq!((heap.depth_of(2), heap.depth_of(7)), (2, 3));
+		assert_eq!((heap.subtree_size(0), heap.subtree_size(group)), (10, 4));
+		assert_eq!(heap.wrap(group, "outer"), 10);
+		assert_eq!(heap.height(), 4);
+		assert_eq!(heap.try_wrap(0, "above root"), Err(HeapError::RootReparent));
+		assert_eq!(heap.try_wrap(42, "nothing"), Err(HeapError::InvalidIndex(42)));
+	}
+	#[test]
+	fn sort_children_test() {
+		let mut heap = tree!("root" => ["c" => ["z", "x", "y"], "a", "b" => ["q", "p"]]);
+		heap.sort_children_by(0, |a, b| a.cmp(b));
+		assert_eq!(heap.children(0).map(|i| heap[i]).collect::<Vec<_>>(), vec!["a", "b", "c"]);
+		assert_eq!(heap.children(1).map(|i| heap[i]).collect::<Vec<_>>(), vec!["z", "x", "y"]);
+		heap.sort_by(|a, b| b.cmp(a));
+		let order: Vec<_> = heap.iter_dfs(0).map(|(_, v)| *v).collect();
+		assert_eq!(order, vec!["root", "c", "z", "y", "x", "b", "q", "p", "a"]);
+		let index = heap.insert("d", 0);
+		assert_eq!(heap.children(0).next_back(), Some(index));
+	}
+	#[test]
+	#[should_panic]
+	fn move_subtree_cycle_test() {
+		let mut heap = make_test_heap();
+		heap.move_subtree(1, 4);
+	}
+	#[test]
 	#[should_panic]
 	fn remove_root_test() {
 		let mut heap = make_test_heap();
@@ -167,7 +1847,7 @@ mod tests {
 
 	#[test]
 	fn get_children_test() {
-		let mut children = HashSet::new();
+		let mut children = IndexSet::new();
 		let mut heap = Heap::new("root");
 		children.insert(heap.insert("first child", 0));
 		children.insert(heap.insert("second child", 0));
@@ -182,7 +1862,7 @@ mod tests {
 
 	#[test]
 	fn get_descendants_test() {
-		let mut children = HashSet::new();
+		let mut children = IndexSet::new();
 		let mut heap = Heap::new("root");
 		heap.insert("first child", 0);
 		heap.insert("second child", 0);
@@ -194,4 +1874,337 @@ mod tests {
 		children.insert(heap.insert("great grandchild", 4));
 		assert_eq!(heap.descendants_of(1), children);
 	}
+
+	#[test]
+	fn get_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.get(0), Some(&"root"));
+		assert_eq!(heap.get(4), Some(&"first grandchild"));
+		assert_eq!(heap.get(100), None);
+		heap.remove(4);
+		assert_eq!(heap.get(4), None);
+		assert_eq!(heap.get(8), None);
+		*heap.get_mut(1).unwrap() = "renamed";
+		assert_eq!(heap.get(1), Some(&"renamed"));
+		assert!(heap.get_mut(4).is_none());
+	}
+
+	#[test]
+	fn get_disjoint_mut_test() {
+		let mut heap = make_test_heap();
+		let [parent, child] = heap.get_disjoint_mut([1, 4]).unwrap();
+		core::mem::swap(parent, child);
+		assert_eq!((heap[1], heap[4]), ("first grandchild", "first child"));
+		assert_eq!(heap.get_disjoint_mut([2, 3, 2]).err(), Some(HeapError::DuplicateIndex(2)));
+		heap.remove(8);
+		assert_eq!(heap.get_disjoint_mut([0, 8]).err(), Some(HeapError::InvalidIndex(8)));
+		assert_eq!(heap.get_disjoint_mut([0, 100]).err(), Some(HeapError::InvalidIndex(100)));
+	}
+
+	#[test]
+	fn replace_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.replace(4, "replaced"), "first grandchild");
+		assert_eq!(heap[4], "replaced");
+		assert_eq!(heap.parent_of(4), Some(1));
+	}
+	#[test]
+	#[should_panic]
+	fn replace_invalid_test() {
+		let mut heap = make_test_heap();
+		heap.remove(4);
+		heap.replace(4, "replaced");
+	}
+
+	#[test]
+	fn position_of_test() {
+		let mut heap = make_test_heap();
+		assert!(heap.contains(&"great grandchild"));
+		assert!(!heap.contains(&"stranger"));
+		assert_eq!(heap.position_of(&"third child"), Some(3));
+		heap.insert("third child", 4);
+		heap.insert("third child", 1);
+		assert_eq!(heap.position_of(&"third child"), Some(9));
+		assert_eq!(heap.position_of(&"stranger"), None);
+	}
+
+	#[test]
+	fn root_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(*heap.root(), "root");
+		*heap.root_mut() = "renamed";
+		assert_eq!(heap.set_root_value("new root"), "renamed");
+		assert_eq!(heap[0], "new root");
+	}
+
+	#[test]
+	fn iter_mut_test() {
+		let mut heap = Heap::new(1);
+		heap.insert(2, 0);
+		let removed = heap.insert(3, 0);
+		heap.insert(4, 1);
+		heap.remove(removed);
+		heap.iter_mut().for_each(|x| *x *= 10);
+		assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec![10, 20, 40]);
+	}
+
+	#[test]
+	fn path_test() {
+		let heap = make_test_heap();
+		assert_eq!(heap.path_to_root(8), vec![8, 4, 1, 0]);
+		assert_eq!(heap.path_to_root(0), vec![0]);
+		assert_eq!(heap.path_between(8, 6), vec![8, 4, 1, 0, 2, 6]);
+		assert_eq!(heap.path_between(5, 8), vec![5, 1, 4, 8]);
+		assert_eq!(heap.path_between(8, 1), vec![8, 4, 1]);
+		assert_eq!(heap.path_between(0, 7), vec![0, 2, 7]);
+		assert_eq!(heap.path_between(3, 3), vec![3]);
+	}
+
+	#[test]
+	fn lowest_common_ancestor_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.lowest_common_ancestor(8, 5), 1);
+		assert_eq!(heap.lowest_common_ancestor(8, 7), 0);
+		assert_eq!(heap.lowest_common_ancestor(4, 8), 4);
+		assert_eq!(heap.lowest_common_ancestor(6, 6), 6);
+		heap.move_subtree(2, 8);
+		assert_eq!(heap.lowest_common_ancestor(7, 5), 1);
+		assert_eq!(heap.lowest_common_ancestor(6, 4), 4);
+	}
+
+	#[test]
+	fn ancestry_test() {
+		let mut heap = make_test_heap();
+		assert!(heap.is_ancestor_of(0, 8));
+		assert!(heap.is_ancestor_of(1, 8));
+		assert!(!heap.is_ancestor_of(2, 8));
+		assert!(!heap.is_ancestor_of(8, 1));
+		assert!(!heap.is_ancestor_of(4, 4));
+		assert!(heap.is_descendant_of(8, 4));
+		assert!(!heap.is_descendant_of(3, 3));
+		heap.move_subtree(1, 7);
+		assert!(heap.is_ancestor_of(2, 8));
+		assert!(heap.is_descendant_of(5, 7));
+
+		// Every change to the shape drops the intervals the checks above built
+		let check = |heap: &Heap<&str>| {
+			let nodes: Vec<_> = heap.iter_dfs(0).map(|(i, _)| i).collect();
+			nodes.iter().all(|&a| nodes.iter().all(|&b| heap.is_ancestor_of(a, b) == heap.ancestors(b).any(|x| x == a)))
+		};
+		heap.remove(4);
+		let new = heap.insert("new", 5);
+		assert!(heap.is_ancestor_of(1, new) && check(&heap));
+		heap.sort_by(|a, b| b.cmp(a));
+		heap.swap_subtrees(new, 3);
+		heap.wrap(new, "wrapper");
+		assert!(check(&heap));
+		heap.reroot(7);
+		assert!(check(&heap));
+		heap.compact();
+		assert!(check(&heap) && check(&heap.clone()));
+
+		// Edits check for cycles without rebuilding the intervals
+		heap.move_subtree(2, 1);
+		assert_eq!(heap.try_move_subtree(1, 2), Err(HeapError::Cycle));
+		assert_eq!(heap.try_swap_subtrees(1, 2), Err(HeapError::Cycle));
+		assert!(!heap.pre_order.is_built());
+		assert!(check(&heap));
+	}
+
+	#[test]
+	fn parent_of_test() {
+		let heap = make_test_heap();
+		assert_eq!(heap.parent_of(0), None);
+		assert_eq!(heap.parent_of(1), Some(0));
+		assert_eq!(heap.parent_of(8), Some(4));
+	}
+	#[test]
+	#[should_panic]
+	fn parent_of_invalid_test() {
+		let mut heap = make_test_heap();
+		heap.remove(4);
+		heap.parent_of(4);
+	}
+
+	#[test]
+	fn structural_eq_test() {
+		let mut heap = make_test_heap();
+		heap.remove(2);
+		let mut other = Heap::new("root");
+		let first = other.insert("first child", 0);
+		let third = other.insert("third child", 0);
+		let grandchild = other.insert("first grandchild", first);
+		other.insert("second grandchild", first);
+		other.insert("great grandchild", grandchild);
+		assert_eq!(heap, other);
+		other[third] = "changed";
+		assert_ne!(heap, other);
+		other[third] = "third child";
+		other.move_subtree(grandchild, third);
+		assert_ne!(heap, other);
+		assert_ne!(heap, make_test_heap());
+	}
+
+	#[test]
+	// The cached pre-order numbers are the only interior mutability, and hashing ignores them
+	#[allow(clippy::mutable_key_type)]
+	fn structural_hash_test() {
+		let mut heap = make_test_heap();
+		heap.remove(2);
+		let mut other = Heap::new("root");
+		let first = other.insert("first child", 0);
+		let third = other.insert("third child", 0);
+		let grandchild = other.insert("first grandchild", first);
+		other.insert("second grandchild", first);
+		other.insert("great grandchild", grandchild);
+		let set = std::collections::HashSet::from([heap]);
+		assert!(set.contains(&other));
+		other.move_subtree(grandchild, third);
+		assert!(!set.contains(&other));
+	}
+
+	#[test]
+	fn max_index_test() {
+		#[cfg(feature = "compact_index")]
+		assert_eq!(MAX_INDEX, u32::MAX as usize - 1);
+		#[cfg(not(feature = "compact_index"))]
+		assert_eq!(MAX_INDEX, usize::MAX - 1);
+		assert!(fits_raw_index(0));
+		assert_eq!(fits_raw_index(u32::MAX as usize), !cfg!(feature = "compact_index"));
+		assert_eq!(Link::new(Some(MAX_INDEX)).get(), Some(MAX_INDEX));
+		assert_eq!(Link::new(Some(0)).get(), Some(0));
+		assert_eq!(Link::new(None).get(), None);
+	}
+
+	#[test]
+	fn memory_usage_test() {
+		assert_eq!(size_of::<Link>(), size_of::<RawIndex>());
+		let mut heap = Heap::with_capacity(16, 0u64);
+		heap.insert(1, 0);
+		assert!(heap.memory_usage() >= 16 * Heap::<u64>::SLOT_SIZE);
+		heap.shrink_to_fit();
+		assert!(heap.memory_usage() < 16 * Heap::<u64>::SLOT_SIZE);
+	}
+
+	#[test]
+	fn compact_test() {
+		let mut heap = make_test_heap();
+		let id = heap.id_of(5).unwrap();
+		heap.remove(1);
+		heap.remove(6);
+		let new_indices = heap.compact();
+		assert_eq!(new_indices, vec![Some(0), None, Some(1), Some(2), None, None, None, Some(3), None]);
+		assert_eq!(heap.len(), 4);
+		assert_eq!(heap.capacity(), 4);
+		assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec!["root", "second child", "third child", "fourth grandchild"]);
+		assert_eq!(heap.parent_of(3), Some(1));
+		let i = heap.insert("new", 3);
+		assert_eq!(i, 4);
+		assert_eq!(heap.get_by_id(id), None);
+		let new_id = heap.id_of(i).unwrap();
+		heap.remove(i);
+		heap.compact();
+		heap.insert("newer", 0);
+		assert_eq!(heap.get_by_id(new_id), None);
+	}
+
+	#[test]
+	fn clear_test() {
+		let mut heap = make_test_heap();
+		let capacity = heap.capacity();
+		let stale = heap.id_of(3).unwrap();
+		heap.clear();
+		assert_eq!((heap.len(), heap.free_slots(), heap.capacity()), (1, 0, capacity));
+		assert!(heap.is_leaf(0));
+		assert_eq!(heap.subtree_size(0), 1);
+		assert_eq!(heap.insert("new", 0), 1);
+		heap.insert("newer", 0);
+		heap.insert("newest", 1);
+		assert!(!heap.contains_id(stale));
+		assert_eq!(heap.reset("new root"), "root");
+		assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec!["new root"]);
+	}
+	#[test]
+	fn fragmentation_test() {
+		let mut heap = make_test_heap();
+		assert_eq!((heap.free_slots(), heap.largest_live_run(), heap.fragmentation()), (0, 9, 0.0));
+		heap.remove(4);
+		heap.remove(6);
+		assert_eq!(heap.free_slots(), 3);
+		assert_eq!(heap.largest_live_run(), 4);
+		assert_eq!(heap.fragmentation(), 3.0 / 9.0);
+		heap.remove(7);
+		assert_eq!(heap.free_slots(), 4);
+		heap.compact();
+		assert_eq!((heap.free_slots(), heap.largest_live_run(), heap.fragmentation()), (0, 5, 0.0));
+	}
+
+	#[test]
+	fn reorder_dfs_test() {
+		let mut heap = make_test_heap();
+		heap.remove(3);
+		let mut new_indices = vec![None; 9];
+		heap.reorder_dfs(|old, new| new_indices[old] = Some(new));
+		assert_eq!(new_indices, vec![Some(0), Some(1), Some(5), None, Some(2), Some(4), Some(6), Some(7), Some(3)]);
+		assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec![
+			"root", "first child", "first grandchild", "great grandchild",
+			"second grandchild", "second child", "third grandchild", "fourth grandchild",
+		]);
+		assert_eq!(heap.parent_of(3), Some(2));
+		assert_eq!(heap.parent_of(7), Some(5));
+		assert_eq!(heap.descendants(1).collect::<Vec<_>>(), vec![2, 3, 4]);
+		assert_eq!(heap.insert("new", 0), 8);
+	}
+
+	#[test]
+	fn try_insert_remove_test() {
+		let mut heap = make_test_heap();
+		assert_eq!(heap.try_insert("orphan", 42), Err(HeapError::InvalidParent(42)));
+		assert_eq!(heap.try_remove(0), Err(HeapError::RootRemoval));
+		assert_eq!(heap.try_remove(42), Err(HeapError::InvalidIndex(42)));
+		assert_eq!(heap.try_remove(4), Ok(vec!["first grandchild", "great grandchild"]));
+		assert_eq!(heap.try_remove(4), Err(HeapError::InvalidIndex(4)));
+		assert_eq!(heap.try_insert("child", 8), Err(HeapError::InvalidParent(8)));
+		let i = heap.try_insert("child", 1).unwrap();
+		assert_eq!(heap.get(i), Some(&"child"));
+	}
+
+	#[test]
+	fn node_id_test() {
+		let mut heap = Heap::new("root");
+		let root = heap.id_of(0).unwrap();
+		let child = heap.insert_id("child", root);
+		assert_eq!(heap.get_by_id(child), Some(&"child"));
+		heap.remove(child.index());
+		assert!(!heap.contains_id(child));
+		let other = heap.insert_id("other", root);
+		assert_eq!(other.index(), child.index());
+		assert_eq!(heap.get_by_id(other), Some(&"other"));
+		assert_ne!(heap.id_of(child.index()), Some(child));
+		assert_eq!(heap.get_by_id(child), None);
+		assert_eq!(heap.get_mut_by_id(child), None);
+	}
+
+	#[test]
+	fn reuse_trailing_slot_test() {
+		let mut heap = make_test_heap();
+		heap.remove(8);
+		heap.remove(7);
+		let i = heap.insert("new", 0);
+		assert_eq!(heap.get(i), Some(&"new"));
+		assert_eq!(heap.len(), 8);
+	}
+
+	#[test]
+	fn lowest_free_index_test() {
+		let mut heap = make_test_heap();
+		heap.remove(6);
+		heap.remove(1);
+		assert_eq!(heap.insert("a", 0), 1);
+		assert_eq!(heap.insert("b", 0), 4);
+		assert_eq!(heap.insert("c", 0), 5);
+		assert_eq!(heap.insert("d", 0), 6);
+		assert_eq!(heap.insert("e", 0), 8);
+		assert_eq!(heap.insert("f", 0), 9);
+	}
 }
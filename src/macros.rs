@@ -0,0 +1,66 @@
+/// Builds a [`Heap`](crate::Heap) from nested syntax, numbering nodes in depth-first pre-order.
+///
+/// ```
+/// let heap = heap::tree!("root" => ["a" => ["a1"], "b"]);
+/// assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec!["root", "a", "a1", "b"]);
+/// assert_eq!(heap.parent_of(2), Some(1));
+/// ```
+#[macro_export]
+macro_rules! tree {
+	($($tree:tt)+) => {
+		$crate::Heap::from_nested($crate::__tree_node!($($tree)+))
+	};
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __tree_node {
+	($value:expr => [$($children:tt)*]) => {
+		$crate::TreeNode::new($value, $crate::__tree_children!([] $($children)*))
+	};
+	($value:expr) => {
+		$crate::TreeNode::leaf($value)
+	};
+}
+
+// Munches one comma-separated child at a time, accumulating the built nodes in the brackets
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __tree_children {
+	([$($done:expr),*]) => {
+		$crate::__private::vec![$($done),*]
+	};
+	([$($done:expr),*] $value:expr => [$($children:tt)*] $(, $($rest:tt)*)?) => {
+		$crate::__tree_children!(
+			[$($done,)* $crate::__tree_node!($value => [$($children)*])] $($($rest)*)?
+		)
+	};
+	([$($done:expr),*] $value:expr $(, $($rest:tt)*)?) => {
+		$crate::__tree_children!([$($done,)* $crate::TreeNode::leaf($value)] $($($rest)*)?)
+	};
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::{Heap, TreeNode};
+
+	#[test]
+	fn tree_macro_test() {
+		let heap = tree!("root" => [
+			"a" => ["a1", "a2" => ["a2x"]],
+			"b",
+			"c" => [],
+		]);
+		assert_eq!(heap.into_nested(), TreeNode::new("root", vec![
+			TreeNode::new("a", vec![
+				TreeNode::leaf("a1"),
+				TreeNode::new("a2", vec![TreeNode::leaf("a2x")]),
+			]),
+			TreeNode::leaf("b"),
+			TreeNode::leaf("c"),
+		]));
+		let single: Heap<i32> = tree!(1 + 1);
+		assert_eq!(single.len(), 1);
+		assert_eq!(single[0], 2);
+	}
+}
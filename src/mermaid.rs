@@ -0,0 +1,59 @@
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display, Write};
+
+use crate::Heap;
+
+impl<T> Heap<T> {
+	/// Renders the heap as a top-down Mermaid flowchart, labelling each node with what
+	/// `label` returns for its value and drawing an edge from every parent to its children
+	pub fn to_mermaid<F: FnMut(&T) -> L, L: Display>(&self, label: F) -> String {
+		let mut out = String::new();
+		self.write_mermaid(&mut out, label).expect("Heap: Internal Error: Writing to a String failed.");
+		out
+	}
+	pub fn write_mermaid<W: Write, F: FnMut(&T) -> L, L: Display>(&self, out: &mut W, mut label: F) -> fmt::Result {
+		writeln!(out, "graph TD")?;
+		for (i, value) in self.iter_dfs(0) {
+			writeln!(out, "\tn{i}[\"{}\"]", escape(&label(value).to_string()))?;
+		}
+		for (parent, child) in self.edges() {
+			writeln!(out, "\tn{parent} --> n{child}")?;
+		}
+		Ok(())
+	}
+}
+
+// Mermaid has no backslash escapes inside quoted labels, only its own entity codes
+fn escape(label: &str) -> String {
+	let mut escaped = String::with_capacity(label.len());
+	for c in label.chars() {
+		match c {
+			'"' => escaped.push_str("#quot;"),
+			'#' => escaped.push_str("#35;"),
+			'\n' => escaped.push_str("<br>"),
+			_ => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::tree;
+
+	#[test]
+	fn to_mermaid_test() {
+		let heap = tree!(("root", 1) => [("say \"hi\"", 2) => [("#b", 3)], ("c", 4)]);
+		assert_eq!(heap.to_mermaid(|&(name, _)| name), concat!(
+			"graph TD\n",
+			"\tn0[\"root\"]\n",
+			"\tn1[\"say #quot;hi#quot;\"]\n",
+			"\tn2[\"#35;b\"]\n",
+			"\tn3[\"c\"]\n",
+			"\tn0 --> n1\n",
+			"\tn1 --> n2\n",
+			"\tn0 --> n3\n",
+		));
+		assert!(heap.to_mermaid(|(_, id)| id * 10).contains("\tn3[\"40\"]\n"));
+	}
+}
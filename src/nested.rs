@@ -0,0 +1,98 @@
+use alloc::vec::Vec;
+
+use crate::Heap;
+
+/// An owned, recursive representation of a tree
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TreeNode<T> {
+	pub value: T,
+	pub children: Vec<TreeNode<T>>,
+}
+
+impl<T> TreeNode<T> {
+	pub fn new(value: T, children: Vec<TreeNode<T>>) -> Self {
+		Self { value, children }
+	}
+	pub fn leaf(value: T) -> Self {
+		Self { value, children: Vec::new() }
+	}
+}
+
+impl<T> Heap<T> {
+	/// Children are ordered as in `Heap::children`
+	pub fn into_nested(mut self) -> TreeNode<T> {
+		let order: Vec<_> = self.iter_post_order(0).map(|(i, _)| i).collect();
+		let mut built: Vec<Option<TreeNode<T>>> = Vec::new();
+		built.resize_with(self.inner.len(), || None);
+		for i in order {
+			let value = self.inner[i].value.take().expect("Heap: Internal Error: Visited a free slot.");
+			let children = self.children(i)
+				.map(|c| built[c].take().expect("Heap: Internal Error: Child visited before parent."))
+				.collect();
+			built[i] = Some(TreeNode { value, children });
+		}
+		built[0].take().expect("Heap: Internal Error: Root was not visited.")
+	}
+	/// Nodes are numbered in depth-first pre-order
+	pub fn from_nested(tree: TreeNode<T>) -> Self {
+		let mut heap = Heap::new(tree.value);
+		for child in tree.children {
+			heap.insert_subtree(0, child);
+		}
+		heap
+	}
+	/// Appends the whole of `tree` as the last child of `parent`, inserting its nodes in
+	/// depth-first pre-order. Returns the index of the subtree's root.
+	/// Panics if `parent` is not the index of a valid node
+	pub fn insert_subtree(&mut self, parent: usize, tree: TreeNode<T>) -> usize {
+		let root = self.insert(tree.value, parent);
+		let mut stack: Vec<_> = tree.children.into_iter().rev().map(|c| (c, root)).collect();
+		while let Some((node, parent)) = stack.pop() {
+			let index = self.insert(node.value, parent);
+			stack.extend(node.children.into_iter().rev().map(|c| (c, index)));
+		}
+		root
+	}
+}
+
+impl<T> From<TreeNode<T>> for Heap<T> {
+	fn from(tree: TreeNode<T>) -> Self {
+		Heap::from_nested(tree)
+	}
+}
+impl<T> From<Heap<T>> for TreeNode<T> {
+	fn from(heap: Heap<T>) -> Self {
+		heap.into_nested()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn nested_round_trip_test() {
+		let tree = TreeNode::new("root", vec![
+			TreeNode::new("a", vec![TreeNode::leaf("a1"), TreeNode::leaf("a2")]),
+			TreeNode::leaf("b"),
+		]);
+		let heap = Heap::from_nested(tree.clone());
+		assert_eq!(heap.iter().copied().collect::<Vec<_>>(), vec!["root", "a", "a1", "a2", "b"]);
+		assert_eq!(heap.parent_of(3), Some(1));
+		assert_eq!(heap.into_nested(), tree);
+	}
+
+	#[test]
+	fn insert_subtree_test() {
+		let mut heap = crate::tree!("root" => ["a" => ["a1"], "b"]);
+		heap.remove(2);
+		let tree = TreeNode::new("x", vec![TreeNode::new("x1", vec![TreeNode::leaf("x11")]), TreeNode::leaf("x2")]);
+		let x = heap.insert_subtree(1, tree.clone());
+		assert_eq!(x, 2);
+		assert_eq!(heap.children(1).collect::<Vec<_>>(), vec![x]);
+		assert_eq!(heap.iter_dfs(x).map(|(_, v)| *v).collect::<Vec<_>>(), vec!["x", "x1", "x11", "x2"]);
+		assert_eq!(heap.depth_of(5), 4);
+		assert_eq!(heap.subtree_size(0), 7);
+		assert_eq!(heap.split_off(x).into_nested(), tree);
+	}
+}
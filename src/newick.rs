@@ -0,0 +1,268 @@
+use alloc::string::String;
+use alloc::vec;
+use core::fmt::{self, Write};
+use core::ops::ControlFlow;
+
+use crate::{Heap, Visitor, WalkControl};
+
+/// The data Newick stores for each node of a tree
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct NewickNode {
+	/// Empty when the node is unlabelled
+	pub name: String,
+	/// The length of the branch leading to the node from its parent
+	pub length: Option<f64>,
+}
+
+impl NewickNode {
+	pub fn new(name: impl Into<String>, length: Option<f64>) -> Self {
+		Self { name: name.into(), length }
+	}
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NewickError {
+	/// The input held `found` at byte `position`, or ended there if `found` is `None`
+	Unexpected { position: usize, found: Option<char> },
+	/// The branch length starting at byte `position` is not a number
+	InvalidLength { position: usize },
+}
+
+impl fmt::Display for NewickError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unexpected { position, found: Some(c) } =>
+				write!(f, "Heap: Error: Unexpected {c:?} at byte {position} of the Newick input"),
+			Self::Unexpected { position, found: None } =>
+				write!(f, "Heap: Error: The Newick input ended early at byte {position}"),
+			Self::InvalidLength { position } =>
+				write!(f, "Heap: Error: Invalid branch length at byte {position} of the Newick input"),
+		}
+	}
+}
+
+impl core::error::Error for NewickError {}
+
+// Characters that end an unquoted label
+const RESERVED: &str = "()[]':;,";
+
+struct Writer<'a, W> {
+	heap: &'a Heap<NewickNode>,
+	out: &'a mut W,
+}
+
+impl<W: Write> Visitor<NewickNode> for Writer<'_, W> {
+	type Break = fmt::Error;
+	fn enter(&mut self, index: usize, _: &NewickNode) -> WalkControl<fmt::Error> {
+		match !self.heap.is_leaf(index) && self.out.write_char('(').is_err() {
+			true => WalkControl::Break(fmt::Error),
+			false => WalkControl::Continue,
+		}
+	}
+	fn leave(&mut self, index: usize, node: &NewickNode) -> ControlFlow<fmt::Error> {
+		match self.close(index, node) {
+			Ok(()) => ControlFlow::Continue(()),
+			Err(e) => ControlFlow::Break(e),
+		}
+	}
+}
+
+impl<W: Write> Writer<'_, W> {
+	// Writes the node's name and length, followed by whatever separates it from the next node
+	fn close(&mut self, index: usize, node: &NewickNode) -> fmt::Result {
+		write_name(self.out, &node.name)?;
+		if let Some(length) = node.length {
+			write!(self.out, ":{length}")?;
+		}
+		let links = &self.heap.inner[index];
+		match (links.next_sibling.get(), links.parent()) {
+			(Some(_), _) => self.out.write_char(','),
+			(None, Some(_)) => self.out.write_char(')'),
+			(None, None) => Ok(()),
+		}
+	}
+}
+
+fn write_name<W: Write>(out: &mut W, name: &str) -> fmt::Result {
+	if !name.chars().any(|c| c.is_whitespace() || RESERVED.contains(c)) {
+		return out.write_str(name);
+	}
+	out.write_char('\'')?;
+	for c in name.chars() {
+		if c == '\'' {
+			out.write_char('\'')?;
+		}
+		out.write_char(c)?;
+	}
+	out.write_char('\'')
+}
+
+struct Parser<'a> {
+	input: &'a str,
+	position: usize,
+}
+
+impl Parser<'_> {
+	fn peek(&self) -> Option<char> {
+		self.input[self.position..].chars().next()
+	}
+	fn bump(&mut self) -> Option<char> {
+		let c = self.peek()?;
+		self.position += c.len_utf8();
+		Some(c)
+	}
+	fn unexpected(&self) -> NewickError {
+		NewickError::Unexpected { position: self.position, found: self.peek() }
+	}
+	// Skips whitespace and bracketed comments
+	fn skip_space(&mut self) -> Result<(), NewickError> {
+		loop {
+			match self.peek() {
+				Some(c) if c.is_whitespace() => {
+					self.bump();
+				}
+				Some('[') => {
+					while self.bump().ok_or_else(|| self.unexpected())? != ']' {}
+				}
+				_ => return Ok(()),
+			}
+		}
+	}
+	fn expect(&mut self, expected: char) -> Result<(), NewickError> {
+		self.skip_space()?;
+		match self.peek() == Some(expected) {
+			true => {
+				self.bump();
+				Ok(())
+			}
+			false => Err(self.unexpected()),
+		}
+	}
+	fn node(&mut self) -> Result<NewickNode, NewickError> {
+		self.skip_space()?;
+		let mut name = String::new();
+		if self.peek() == Some('\'') {
+			self.bump();
+			loop {
+				match self.bump().ok_or_else(|| self.unexpected())? {
+					'\'' if self.peek() == Some('\'') => {
+						self.bump();
+						name.push('\'');
+					}
+					'\'' => break,
+					c => name.push(c),
+				}
+			}
+		} else {
+			while let Some(c) = self.peek().filter(|&c| !c.is_whitespace() && !RESERVED.contains(c)) {
+				name.push(c);
+				self.bump();
+			}
+		}
+		self.skip_space()?;
+		let mut length = None;
+		if self.peek() == Some(':') {
+			self.bump();
+			self.skip_space()?;
+			let start = self.position;
+			while self.peek().is_some_and(|c| !c.is_whitespace() && !RESERVED.contains(c)) {
+				self.bump();
+			}
+			let parsed = self.input[start..self.position].parse();
+			length = Some(parsed.map_err(|_| NewickError::InvalidLength { position: start })?);
+		}
+		Ok(NewickNode { name, length })
+	}
+}
+
+impl Heap<NewickNode> {
+	/// Renders the heap as a Newick tree ending in `;`. Names are quoted when they contain
+	/// whitespace or characters Newick reserves, and only given branch lengths are written.
+	pub fn to_newick(&self) -> String {
+		let mut out = String::new();
+		self.write_newick(&mut out).expect("Heap: Internal Error: Writing to a String failed.");
+		out
+	}
+	pub fn write_newick<W: Write>(&self, out: &mut W) -> fmt::Result {
+		if let Some(e) = self.walk(0, &mut Writer { heap: self, out }) {
+			return Err(e);
+		}
+		out.write_char(';')
+	}
+
+	/// Parses a single Newick tree, numbering its nodes in depth-first pre-order.
+	/// Names may be quoted with `'`, and unquoted names are taken as they are, so underscores
+	/// stay underscores. Bracketed comments are skipped.
+	pub fn from_newick(input: &str) -> Result<Self, NewickError> {
+		let mut parser = Parser { input, position: 0 };
+		let mut heap = Heap::new(NewickNode::default());
+		// The nodes whose subtrees are still being read
+		let mut open = vec![0];
+		'subtree: loop {
+			parser.skip_space()?;
+			let current = *open.last().expect("Heap: Internal Error: No open node.");
+			if parser.peek() == Some('(') {
+				parser.bump();
+				open.push(heap.insert(NewickNode::default(), current));
+				continue;
+			}
+			// The node's children are done, so its name and length follow
+			loop {
+				let current = open.pop().expect("Heap: Internal Error: No open node.");
+				heap[current] = parser.node()?;
+				let Some(&parent) = open.last() else {
+					break 'subtree;
+				};
+				parser.skip_space()?;
+				match parser.peek() {
+					Some(',') => {
+						parser.bump();
+						open.push(heap.insert(NewickNode::default(), parent));
+						continue 'subtree;
+					}
+					Some(')') => {
+						parser.bump();
+					}
+					_ => return Err(parser.unexpected()),
+				}
+			}
+		}
+		parser.expect(';')?;
+		parser.skip_space()?;
+		match parser.peek() {
+			None => Ok(heap),
+			Some(_) => Err(parser.unexpected()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use super::{NewickError, NewickNode};
+	use crate::Heap;
+
+	#[test]
+	fn newick_round_trip_test() {
+		let text = "((A:0.1,B:0.25)E:0.5,'C d':1,(F,'it''s')):0;";
+		let heap = Heap::from_newick(text).unwrap();
+		let names: Vec<_> = heap.iter_dfs(0).map(|(_, n)| n.name.as_str()).collect();
+		assert_eq!(names, ["", "E", "A", "B", "C d", "", "F", "it's"]);
+		assert_eq!(heap[0].length, Some(0.0));
+		assert_eq!(heap[3], NewickNode::new("B", Some(0.25)));
+		assert_eq!(heap[6].length, None);
+		assert_eq!(heap.to_newick(), text);
+		let spaced = Heap::from_newick(" ( A [comment] , B ) root ;\n").unwrap();
+		assert_eq!(spaced.to_newick(), "(A,B)root;");
+		assert_eq!(Heap::from_newick("leaf;").unwrap().len(), 1);
+	}
+
+	#[test]
+	fn newick_errors_test() {
+		assert_eq!(Heap::from_newick("(A,B"), Err(NewickError::Unexpected { position: 4, found: None }));
+		assert_eq!(Heap::from_newick("(A:x,B);"), Err(NewickError::InvalidLength { position: 3 }));
+		assert_eq!(Heap::from_newick("(A,B);C"), Err(NewickError::Unexpected { position: 6, found: Some('C') }));
+		assert_eq!(Heap::from_newick("(A)B"), Err(NewickError::Unexpected { position: 4, found: None }));
+	}
+}
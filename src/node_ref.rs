@@ -0,0 +1,152 @@
+use crate::Heap;
+
+/// A shared handle to a valid node, for navigating the tree without juggling raw indices.
+/// Created by [`Heap::node`].
+pub struct NodeRef<'a, T> {
+	heap: &'a Heap<T>,
+	index: usize,
+}
+
+// Derived impls would needlessly require `T: Clone`
+impl<T> Clone for NodeRef<'_, T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+impl<T> Copy for NodeRef<'_, T> {}
+
+impl<'a, T> NodeRef<'a, T> {
+	pub fn index(&self) -> usize {
+		self.index
+	}
+	pub fn value(&self) -> &'a T {
+		&self.heap[self.index]
+	}
+	/// Returns `None` for the root node
+	pub fn parent(&self) -> Option<NodeRef<'a, T>> {
+		self.heap.parent_of(self.index).map(|index| self.heap.node_unchecked(index))
+	}
+	pub fn children(&self) -> impl DoubleEndedIterator<Item = NodeRef<'a, T>> + 'a {
+		let heap = self.heap;
+		heap.children(self.index).map(move |index| heap.node_unchecked(index))
+	}
+	/// Yields the node's ancestors from its parent up to the root
+	pub fn ancestors(&self) -> impl Iterator<Item = NodeRef<'a, T>> + 'a {
+		let heap = self.heap;
+		heap.ancestors(self.index).map(move |index| heap.node_unchecked(index))
+	}
+	/// Yields the node's descendants in depth-first pre-order
+	pub fn descendants(&self) -> impl Iterator<Item = NodeRef<'a, T>> + 'a {
+		let heap = self.heap;
+		heap.descendants(self.index).map(move |index| heap.node_unchecked(index))
+	}
+	pub fn is_leaf(&self) -> bool {
+		self.heap.is_leaf(self.index)
+	}
+	pub fn depth(&self) -> usize {
+		self.heap.depth_of(self.index)
+	}
+}
+
+/// An exclusive handle to a valid node, for editing the tree around it.
+/// Created by [`Heap::node_mut`].
+pub struct NodeMut<'a, T> {
+	heap: &'a mut Heap<T>,
+	index: usize,
+}
+
+impl<'a, T> NodeMut<'a, T> {
+	pub fn index(&self) -> usize {
+		self.index
+	}
+	pub fn value(&self) -> &T {
+		&self.heap[self.index]
+	}
+	pub fn value_mut(&mut self) -> &mut T {
+		&mut self.heap[self.index]
+	}
+	pub fn into_value(self) -> &'a mut T {
+		&mut self.heap[self.index]
+	}
+	/// Returns the previous value
+	pub fn set_value(&mut self, value: T) -> T {
+		self.heap.replace(self.index, value)
+	}
+	pub fn as_ref(&self) -> NodeRef<'_, T> {
+		self.heap.node_unchecked(self.index)
+	}
+	/// Returns `None` for the root node
+	pub fn parent(&mut self) -> Option<NodeMut<'_, T>> {
+		let index = self.heap.parent_of(self.index)?;
+		Some(NodeMut { heap: self.heap, index })
+	}
+	/// Inserts `value` as the last child of this node and returns a handle to it
+	pub fn append(&mut self, value: T) -> NodeMut<'_, T> {
+		let index = self.heap.insert(value, self.index);
+		NodeMut { heap: self.heap, index }
+	}
+	/// Removes this node and its descendants from the heap, returning them as a new heap.
+	/// Panics if this is the root node
+	pub fn detach(self) -> Heap<T> {
+		self.heap.split_off(self.index)
+	}
+}
+
+impl<T> Heap<T> {
+	/// Returns `None` if `index` is invalid
+	pub fn node(&self, index: usize) -> Option<NodeRef<'_, T>> {
+		self.is_valid_idx(index).then(|| self.node_unchecked(index))
+	}
+	/// Returns `None` if `index` is invalid
+	pub fn node_mut(&mut self, index: usize) -> Option<NodeMut<'_, T>> {
+		self.is_valid_idx(index).then_some(NodeMut { heap: self, index })
+	}
+	fn node_unchecked(&self, index: usize) -> NodeRef<'_, T> {
+		NodeRef { heap: self, index }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+	use alloc::vec::Vec;
+
+	use crate::Heap;
+
+	#[test]
+	fn node_ref_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		heap.insert("b", 0);
+		let a1 = heap.insert("a1", a);
+		let node = heap.node(a1).unwrap();
+		assert_eq!(*node.value(), "a1");
+		assert_eq!(node.depth(), 2);
+		assert!(node.is_leaf());
+		let parent = node.parent().unwrap();
+		assert_eq!(parent.index(), a);
+		assert_eq!(node.ancestors().map(|n| *n.value()).collect::<Vec<_>>(), vec!["a", "root"]);
+		let root = heap.node(0).unwrap();
+		assert!(root.parent().is_none());
+		assert_eq!(root.children().rev().map(|n| *n.value()).collect::<Vec<_>>(), vec!["b", "a"]);
+		assert_eq!(root.descendants().map(|n| n.index()).collect::<Vec<_>>(), vec![a, a1, 2]);
+		assert!(heap.node(42).is_none());
+	}
+
+	#[test]
+	fn node_mut_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let mut node = heap.node_mut(a).unwrap();
+		assert_eq!(node.set_value("renamed"), "a");
+		let a1 = node.append("a1").index();
+		*node.append("a2").value_mut() = "second";
+		assert_eq!(*node.value(), "renamed");
+		assert_eq!(*node.parent().unwrap().value(), "root");
+		assert_eq!(node.as_ref().children().count(), 2);
+		let detached = heap.node_mut(a1).unwrap().detach();
+		assert_eq!(detached[0], "a1");
+		assert_eq!(heap.children(a).map(|i| heap[i]).collect::<Vec<_>>(), vec!["second"]);
+		assert_eq!(heap[a], "renamed");
+	}
+}
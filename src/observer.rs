@@ -0,0 +1,139 @@
+use alloc::boxed::Box;
+use core::fmt;
+
+use crate::Heap;
+
+/// Callbacks the heap makes while it is being mutated, so that outside state can be kept in
+/// sync. Every method does nothing by default.
+pub trait HeapObserver {
+	/// Called after a node has been inserted at `index`
+	fn on_insert(&mut self, _index: usize) {}
+	/// Called for every node that leaves the heap, including those moved out by `split_off`
+	fn on_remove(&mut self, _index: usize) {}
+	/// Called after the node at `index` has been attached under `new_parent`, which may be
+	/// the same node as `old_parent` when only its position among the children changed.
+	/// `reroot` reports the old root, which had no parent, with `old_parent == new_parent`.
+	fn on_reparent(&mut self, _index: usize, _old_parent: usize, _new_parent: usize) {}
+	/// Called for every node moved to another slot: by `compact` and `reorder_dfs`, by
+	/// `promote_to_root` for the promoted node, and by `reroot` for the new and old roots
+	fn on_reindex(&mut self, _old: usize, _new: usize) {}
+}
+
+/// An installed observer, as `set_observer` and `take_observer` hand it back
+pub type BoxedObserver = Box<dyn HeapObserver + Send + Sync>;
+
+#[derive(Default)]
+pub(crate) struct Observer(Option<BoxedObserver>);
+
+impl Observer {
+	pub(crate) fn notify(&mut self, f: impl FnOnce(&mut dyn HeapObserver)) {
+		if let Some(observer) = &mut self.0 {
+			f(observer.as_mut());
+		}
+	}
+}
+
+// A cloned heap is mutated separately, so it must not report to the original's observer
+impl Clone for Observer {
+	fn clone(&self) -> Self {
+		Self::default()
+	}
+}
+
+impl fmt::Debug for Observer {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.0 {
+			Some(_) => f.write_str("Observer(set)"),
+			None => f.write_str("Observer(none)"),
+		}
+	}
+}
+
+impl<T> Heap<T> {
+	/// Installs `observer`, replacing and returning any previous one.
+	/// Clones of the heap start without an observer.
+	pub fn set_observer<O: HeapObserver + Send + Sync + 'static>(&mut self, observer: O) -> Option<BoxedObserver> {
+		self.observer.0.replace(Box::new(observer))
+	}
+	pub fn take_observer(&mut self) -> Option<BoxedObserver> {
+		self.observer.0.take()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::sync::Arc;
+	use alloc::vec;
+	use alloc::vec::Vec;
+	use std::sync::Mutex;
+
+	use super::HeapObserver;
+	use crate::Heap;
+
+	#[derive(Clone, Debug, PartialEq)]
+	enum Event {
+		Insert(usize),
+		Remove(usize),
+		Reparent(usize, usize, usize),
+		Reindex(usize, usize),
+	}
+
+	struct Recorder(Arc<Mutex<Vec<Event>>>);
+
+	impl HeapObserver for Recorder {
+		fn on_insert(&mut self, index: usize) {
+			self.0.lock().unwrap().push(Event::Insert(index));
+		}
+		fn on_remove(&mut self, index: usize) {
+			self.0.lock().unwrap().push(Event::Remove(index));
+		}
+		fn on_reparent(&mut self, index: usize, old_parent: usize, new_parent: usize) {
+			self.0.lock().unwrap().push(Event::Reparent(index, old_parent, new_parent));
+		}
+		fn on_reindex(&mut self, old: usize, new: usize) {
+			self.0.lock().unwrap().push(Event::Reindex(old, new));
+		}
+	}
+
+	#[test]
+	fn observer_test() {
+		let events = Arc::new(Mutex::new(Vec::new()));
+		let mut heap = Heap::new("root");
+		assert!(heap.set_observer(Recorder(events.clone())).is_none());
+		let a = heap.insert("a", 0);
+		let b = heap.insert("b", 0);
+		let a1 = heap.insert("a1", a);
+		heap.move_subtree(a1, b);
+		heap.clone().remove(b);
+		heap.remove(a);
+		heap.compact();
+		assert!(heap.take_observer().is_some());
+		heap.insert("unobserved", 0);
+		assert_eq!(*events.lock().unwrap(), vec![
+			Event::Insert(1),
+			Event::Insert(2),
+			Event::Insert(3),
+			Event::Reparent(3, 1, 2),
+			Event::Remove(1),
+			Event::Reindex(2, 1),
+			Event::Reindex(3, 2),
+		]);
+	}
+
+	#[test]
+	fn reroot_observer_test() {
+		let events = Arc::new(Mutex::new(Vec::new()));
+		let mut heap = crate::tree!("root" => ["a" => ["b" => ["c"]], "d"]);
+		heap.set_observer(Recorder(events.clone()));
+		heap.reroot(3);
+		// Each node on the path moves under its old child, reported with its old indices, and
+		// the old root reports its new parent as its old one
+		assert_eq!(*events.lock().unwrap(), vec![
+			Event::Reparent(2, 1, 3),
+			Event::Reparent(1, 0, 2),
+			Event::Reparent(0, 1, 1),
+			Event::Reindex(3, 0),
+			Event::Reindex(0, 3),
+		]);
+	}
+}
@@ -0,0 +1,162 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use rayon::iter::{walk_tree_prefix, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::Heap;
+
+// Subtrees with at most this many nodes are folded without spawning further tasks
+const SEQUENTIAL_CUTOFF: usize = 1024;
+
+impl<T: Sync> Heap<T> {
+	/// Iterates in parallel over the values of valid nodes
+	pub fn par_iter(&self) -> impl ParallelIterator<Item = &T> {
+		self.inner.par_iter().filter_map(|node| node.value.as_ref())
+	}
+
+	/// Iterates in parallel over the subtree rooted at `start`, splitting the work between
+	/// threads at its branches. Collecting the iterator keeps depth-first pre-order.
+	/// Panics if `start` is invalid
+	pub fn par_iter_subtree(&self, start: usize) -> impl ParallelIterator<Item = (usize, &T)> {
+		assert!(self.is_valid_idx(start), "Heap: Error: Tried to traverse from an invalid node");
+		walk_tree_prefix(start, |&i| self.children(i)).map(|i| (i, &self[i]))
+	}
+
+	/// Reduces the subtree rooted at `start` bottom-up: `f` gets each node's index and value
+	/// along with the results of its children, in the same order as `children`, and its
+	/// result is passed on to the node's parent. Returns the result for `start`.
+	/// Large subtrees are split between threads with `rayon::scope`, while subtrees of up to
+	/// 1024 nodes are folded on a single thread.
+	/// Panics if `start` is invalid
+	pub fn par_fold_subtrees<R, F>(&self, start: usize, f: F) -> R
+	where
+		R: Send,
+		F: Fn(usize, &T, Vec<R>) -> R + Sync,
+	{
+		assert!(self.is_valid_idx(start), "Heap: Error: Tried to fold from an invalid node");
+		self.par_fold_from(start, &f)
+	}
+
+	// Follows the path of largest children down from `index` without recursing, folding the
+	// other children of the path in parallel. Each of those has at most half the nodes of its
+	// parent, so recursion depth stays logarithmic even for long chains
+	fn par_fold_from<R, F>(&self, index: usize, f: &F) -> R
+	where
+		R: Send,
+		F: Fn(usize, &T, Vec<R>) -> R + Sync,
+	{
+		if self.subtree_size(index) <= SEQUENTIAL_CUTOFF {
+			return self.fold_from(index, f);
+		}
+		let mut path = vec![index];
+		while let Some(heavy) = self.children(path[path.len() - 1]).max_by_key(|&c| self.subtree_size(c)) {
+			path.push(heavy);
+		}
+		let light: Vec<usize> = path.windows(2)
+			.flat_map(|pair| self.children(pair[0]).filter(move |&c| c != pair[1]))
+			.collect();
+		let mut folded: Vec<Option<R>> = Vec::new();
+		folded.resize_with(light.len(), || None);
+		rayon::scope(|s| {
+			for (&child, slot) in light.iter().zip(folded.iter_mut()) {
+				s.spawn(move |_| *slot = Some(self.par_fold_from(child, f)));
+			}
+		});
+		let mut folded = folded.into_iter().rev()
+			.map(|result| result.expect("Heap: Internal Error: Light child was not folded."));
+		let mut heavy_result = None;
+		for (depth, &i) in path.iter().enumerate().rev() {
+			let heavy = path.get(depth + 1);
+			// Light results are taken last to first, so children are too
+			let mut children: Vec<R> = self.children(i).rev().map(|c| match heavy {
+				Some(&h) if h == c => heavy_result.take().expect("Heap: Internal Error: Heavy child was not folded."),
+				_ => folded.next().expect("Heap: Internal Error: Light child was not folded."),
+			}).collect();
+			children.reverse();
+			heavy_result = Some(f(i, &self[i], children));
+		}
+		heavy_result.expect("Heap: Internal Error: Root was not folded.")
+	}
+
+	// Folds a subtree on the current thread, keeping pending results on a stack: in
+	// post-order a node's children are the topmost results when it is reached
+	fn fold_from<R, F>(&self, index: usize, f: &F) -> R
+	where
+		F: Fn(usize, &T, Vec<R>) -> R,
+	{
+		let mut pending: Vec<R> = Vec::new();
+		for (i, value) in self.iter_post_order(index) {
+			let children = pending.split_off(pending.len() - self.child_count(i));
+			pending.push(f(i, value, children));
+		}
+		pending.pop().expect("Heap: Internal Error: Root was not folded.")
+	}
+}
+
+impl<T: Send> Heap<T> {
+	/// Iterates mutably in parallel over the values of valid nodes
+	pub fn par_iter_mut(&mut self) -> impl ParallelIterator<Item = &mut T> {
+		self.inner.par_iter_mut().filter_map(|node| node.value.as_mut())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::format;
+	use alloc::string::String;
+	use alloc::vec;
+	use alloc::vec::Vec;
+
+	use rayon::iter::ParallelIterator;
+
+	use crate::{tree, Heap};
+
+	#[test]
+	fn par_iter_test() {
+		let mut heap = Heap::new(0);
+		for i in 1..1000 {
+			heap.insert(i, (i - 1) / 3);
+		}
+		heap.remove(1);
+		assert_eq!(heap.par_iter().sum::<usize>(), heap.iter().sum::<usize>());
+		heap.par_iter_mut().for_each(|value| *value *= 2);
+		assert_eq!(heap.par_iter().sum::<usize>(), heap.iter().sum::<usize>());
+		assert_eq!(heap[2], 4);
+	}
+
+	#[test]
+	fn par_iter_subtree_test() {
+		let heap = tree!("root" => ["a" => ["a1", "a2" => ["a21"]], "b" => ["b1"]]);
+		let visited: Vec<_> = heap.par_iter_subtree(0).collect();
+		assert_eq!(visited, heap.iter_dfs(0).collect::<Vec<_>>());
+		assert_eq!(heap.par_iter_subtree(1).count(), 4);
+	}
+
+	#[test]
+	fn par_fold_subtrees_test() {
+		let heap = tree!(1 => [2 => [4, 5 => [7]], 3 => [6]]);
+		let sums = heap.par_fold_subtrees(0, |_, value, children| value + children.iter().sum::<i32>());
+		assert_eq!(sums, 28);
+		let nested = heap.par_fold_subtrees(1, |_, value, children: Vec<String>| {
+			format!("{value}({})", children.join(" "))
+		});
+		assert_eq!(nested, "2(4() 5(7()))");
+		let mut chain = Heap::new(0);
+		for i in 1..10_000 {
+			chain.insert(i, i - 1);
+		}
+		assert_eq!(chain.par_fold_subtrees(0, |_, _, children: Vec<usize>| children.first().map_or(1, |h| h + 1)), 10_000);
+		let mut wide = Heap::new(0);
+		for i in 1..20_000 {
+			wide.insert(i, if i % 7 == 0 { i - 1 } else { (i - 1) / 3 });
+		}
+		wide.remove(5);
+		let order = wide.par_fold_subtrees(0, |i, _, children: Vec<Vec<usize>>| {
+			let mut order = vec![i];
+			order.extend(children.into_iter().flatten());
+			order
+		});
+		assert_eq!(order, wide.iter_dfs(0).map(|(i, _)| i).collect::<Vec<_>>());
+		assert_eq!(wide.par_fold_subtrees(0, |_, value, children: Vec<usize>| value + children.iter().sum::<usize>()), wide.iter().sum::<usize>());
+	}
+}
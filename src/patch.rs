@@ -0,0 +1,129 @@
+use core::fmt;
+
+use crate::observer::Observer;
+use crate::weak::Watchers;
+use crate::{nodes_with_capacity, EditNode, Heap, HeapError, Map, TreeEdit};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PatchError {
+	/// Step `edit` of the script could not be carried out
+	Heap { edit: usize, error: HeapError },
+	/// Step `edit` of the script attaches to a node that no earlier step inserted
+	UnknownNewNode { edit: usize, from: usize },
+}
+
+impl fmt::Display for PatchError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Heap { edit, error } => write!(f, "{error} in step {edit} of the patch"),
+			Self::UnknownNewNode { edit, from } => {
+				write!(f, "Heap: Error: Step {edit} of the patch refers to node {from}, which was never inserted")
+			}
+		}
+	}
+}
+
+impl core::error::Error for PatchError {}
+
+impl<T: Clone> Heap<T> {
+	/// Applies an edit script such as one produced by `diff`, either entirely or not at all.
+	/// The whole script is first checked against a copy of the heap's structure without its
+	/// values, so the heap is only mutated once every step is known to succeed.
+	pub fn apply(&mut self, patch: &[TreeEdit<T>]) -> Result<(), PatchError> {
+		apply_to(&mut self.skeleton(), patch, |_| ())?;
+		apply_to(self, patch, T::clone)
+			.expect("Heap: Internal Error: A checked patch failed.");
+		Ok(())
+	}
+}
+
+impl<T> Heap<T> {
+	// A heap with the same slots, links and free list, but no values
+	fn skeleton(&self) -> Heap<()> {
+		let mut inner = nodes_with_capacity(self.inner.len());
+		inner.extend(self.inner.iter().map(|node| node.with_value(node.value.as_ref().map(|_| ()))));
+		Heap {
+			inner,
+			free: self.free.clone(),
+			len: self.len,
+			min_generation: self.min_generation,
+			watchers: Watchers::default(),
+			observer: Observer::default(),
+			pre_order: Default::default(),
+		}
+	}
+}
+
+fn apply_to<T, V, F: FnMut(&T) -> V>(heap: &mut Heap<V>, patch: &[TreeEdit<T>], mut value: F) -> Result<(), PatchError> {
+	// Where each node added by the script ended up
+	let mut inserted: Map<usize, usize> = Map::new();
+	for (edit, step) in patch.iter().enumerate() {
+		let resolve = |node: EditNode| match node {
+			EditNode::Old(index) => Ok(index),
+			EditNode::New(from) => inserted.get(&from).copied().ok_or(PatchError::UnknownNewNode { edit, from }),
+		};
+		let failed = |error| PatchError::Heap { edit, error };
+		match step {
+			TreeEdit::Insert { from, parent, value: v } => {
+				let index = heap.try_insert(value(v), resolve(*parent)?).map_err(failed)?;
+				inserted.insert(*from, index);
+			}
+			TreeEdit::Move { index, parent } => {
+				heap.try_move_subtree(*index, resolve(*parent)?).map_err(failed)?;
+			}
+			TreeEdit::Remove { index } => {
+				heap.try_remove(*index).map_err(failed)?;
+			}
+			TreeEdit::Update { index, value: v } => match heap.get_mut(*index) {
+				Some(slot) => *slot = value(v),
+				None => return Err(failed(HeapError::InvalidIndex(*index))),
+			},
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use super::PatchError;
+	use crate::{tree, EditNode, HeapError, TreeEdit};
+
+	#[test]
+	fn apply_diff_test() {
+		let mut old = tree!((0, "root") => [(1, "a") => [(3, "a1")], (2, "b") => [(4, "b1")]]);
+		let new = tree!((0, "root") => [(2, "b"), (1, "A") => [(4, "b1"), (5, "c") => [(6, "c1")]]]);
+		let patch = old.diff(&new, |&(id, _)| id);
+		old.apply(&patch).unwrap();
+		assert_eq!(old, new);
+
+		let mut old = tree!("root" => ["a", "b" => ["b1"]]);
+		let new = tree!("root" => ["b1" => ["b", "x"], "a"]);
+		old.apply(&old.diff(&new, |&v| v)).unwrap();
+		assert_eq!(old, new);
+	}
+
+	#[test]
+	fn apply_is_atomic_test() {
+		let mut heap = tree!("root" => ["a" => ["a1"], "b"]);
+		let before = heap.clone();
+		let patch = vec![
+			TreeEdit::Update { index: 1, value: "changed" },
+			TreeEdit::Insert { from: 7, parent: EditNode::Old(3), value: "new" },
+			TreeEdit::Move { index: 1, parent: EditNode::New(7) },
+		];
+		assert_eq!(heap.apply(&patch), Ok(()));
+		assert_eq!(heap, tree!("root" => ["b" => ["new" => ["changed" => ["a1"]]]]));
+		heap = before.clone();
+		let bad = [patch.clone(), vec![TreeEdit::Move { index: 3, parent: EditNode::Old(2) }]].concat();
+		assert_eq!(heap.apply(&bad), Err(PatchError::Heap { edit: 3, error: HeapError::Cycle }));
+		assert_eq!(heap, before);
+		let bad = [TreeEdit::Remove { index: 2 }, TreeEdit::Move { index: 1, parent: EditNode::New(9) }];
+		assert_eq!(heap.apply(&bad), Err(PatchError::UnknownNewNode { edit: 1, from: 9 }));
+		let bad = [TreeEdit::Remove { index: 2 }, TreeEdit::Update { index: 2, value: "gone" }];
+		assert_eq!(heap.apply(&bad), Err(PatchError::Heap { edit: 1, error: HeapError::InvalidIndex(2) }));
+		assert_eq!(heap, before);
+		assert_eq!(heap.len(), 4);
+	}
+}
@@ -0,0 +1,96 @@
+use core::borrow::Borrow;
+
+use crate::Heap;
+
+impl<T> Heap<T> {
+	// The first child of `parent` whose value `matches`
+	fn find_child<M: Fn(&T) -> bool>(&self, parent: usize, matches: M) -> Option<usize> {
+		assert!(self.is_valid_idx(parent), "Heap: Error: Tried to insert with invalid parent");
+		self.children(parent).find(|&child| matches(&self[child]))
+	}
+	/// Returns the first child of `parent` whose value has the same `key` as `value`, or
+	/// appends `value` as a new last child of `parent` if there is none. The existing child
+	/// keeps its value, and `value` is dropped.
+	/// Panics if `parent` is not the index of a valid node
+	pub fn get_or_insert_child_by_key<K: PartialEq, F: Fn(&T) -> K>(&mut self, parent: usize, value: T, key: F) -> usize {
+		let wanted = key(&value);
+		match self.find_child(parent, |child| key(child) == wanted) {
+			Some(child) => child,
+			None => self.insert(value, parent),
+		}
+	}
+}
+
+impl<T: PartialEq> Heap<T> {
+	/// Returns the first child of `parent` whose value equals `value`, or appends `value` as
+	/// a new last child of `parent` if there is none.
+	/// Panics if `parent` is not the index of a valid node
+	pub fn get_or_insert_child(&mut self, parent: usize, value: T) -> usize {
+		match self.find_child(parent, |child| *child == value) {
+			Some(child) => child,
+			None => self.insert(value, parent),
+		}
+	}
+}
+
+impl<T: Borrow<str> + for<'a> From<&'a str>> Heap<T> {
+	/// Follows the `/`-separated components of `path` down from the root, reusing the first
+	/// child that matches each component and inserting one where none does, so inserting
+	/// `a/b/c` and then `a/b/d` gives `b` two children. Empty components are skipped, so
+	/// leading, trailing and doubled slashes make no difference.
+	/// Returns the index of the node for the last component, or 0 if there is none.
+	pub fn insert_path(&mut self, path: &str) -> usize {
+		path.split('/').filter(|component| !component.is_empty()).fold(0, |parent, component| {
+			match self.find_child(parent, |child| child.borrow() == component) {
+				Some(child) => child,
+				None => self.insert(T::from(component), parent),
+			}
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+	use alloc::vec::Vec;
+
+	use crate::{tree, Heap};
+
+	#[test]
+	fn insert_path_test() {
+		let mut heap = Heap::new(String::new());
+		let c = heap.insert_path("a/b/c");
+		assert_eq!(heap.insert_path("/a/b//d/"), 4);
+		assert_eq!(heap.insert_path("a/b/c"), c);
+		heap.insert_path("e");
+		assert_eq!(heap.insert_path(""), 0);
+		let names: Vec<_> = heap.iter_dfs(0).map(|(i, name)| (name.as_str(), heap.parent_of(i))).collect();
+		assert_eq!(names, [("", None), ("a", Some(0)), ("b", Some(1)), ("c", Some(2)), ("d", Some(2)), ("e", Some(0))]);
+	}
+
+	#[test]
+	fn get_or_insert_child_test() {
+		let mut heap = tree!("root" => ["a" => ["x"], "b", "a"]);
+		assert_eq!(heap.get_or_insert_child(0, "a"), 1);
+		assert_eq!(heap.get_or_insert_child(0, "c"), 5);
+		assert_eq!(heap.get_or_insert_child(5, "c"), 6);
+		assert_eq!(heap.len(), 7);
+	}
+
+	#[test]
+	fn get_or_insert_child_by_key_test() {
+		// A trie of words, one letter per node, counting the words that pass through each node
+		let mut trie = Heap::new(('^', 0));
+		for word in ["tea", "ten", "to", "tea"] {
+			let mut node = 0;
+			for letter in word.chars() {
+				node = trie.get_or_insert_child_by_key(node, (letter, 0), |&(l, _)| l);
+				trie[node].1 += 1;
+			}
+		}
+		let nodes: Vec<_> = trie.iter_dfs(0).map(|(_, &node)| node).collect();
+		assert_eq!(nodes, [('^', 0), ('t', 4), ('e', 3), ('a', 2), ('n', 1), ('o', 1)]);
+		assert_eq!(trie.get_or_insert_child_by_key(1, ('e', 99), |&(l, _)| l), 2);
+		assert_eq!(trie[2], ('e', 3));
+	}
+}
@@ -0,0 +1,173 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::Heap;
+
+/// A tree whose clones share all of their nodes, so taking a snapshot is O(1).
+/// Editing a node copies only the nodes on the path from the root down to it, and only if
+/// they are shared with another snapshot; every other subtree stays shared.
+/// Nodes are addressed by paths: the position of each child to descend into, starting from
+/// the root, so the empty path is the root itself.
+#[derive(Debug)]
+pub struct PersistentHeap<T> {
+	root: Arc<PersistentNode<T>>,
+	len: usize,
+}
+
+// Derived impls would needlessly require `T: Clone`
+impl<T> Clone for PersistentHeap<T> {
+	fn clone(&self) -> Self {
+		Self { root: self.root.clone(), len: self.len }
+	}
+}
+
+#[derive(Clone, Debug)]
+pub struct PersistentNode<T> {
+	value: T,
+	children: Vec<Arc<PersistentNode<T>>>,
+}
+
+impl<T> PersistentNode<T> {
+	pub fn value(&self) -> &T {
+		&self.value
+	}
+	pub fn children(&self) -> impl DoubleEndedIterator<Item = &PersistentNode<T>> {
+		self.children.iter().map(|child| &**child)
+	}
+	/// Returns `None` if the node has no child at `position`
+	pub fn child(&self, position: usize) -> Option<&PersistentNode<T>> {
+		self.children.get(position).map(|child| &**child)
+	}
+	fn count(&self) -> usize {
+		1 + self.children.iter().map(|child| child.count()).sum::<usize>()
+	}
+}
+
+impl<T> PersistentHeap<T> {
+	pub fn new(root: T) -> Self {
+		Self { root: Arc::new(PersistentNode { value: root, children: Vec::new() }), len: 1 }
+	}
+	// A persistent heap always contains its root, so it can never be empty
+	#[allow(clippy::len_without_is_empty)]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+	pub fn root(&self) -> &PersistentNode<T> {
+		&self.root
+	}
+	/// Returns `None` if `path` does not lead to a node
+	pub fn node(&self, path: &[usize]) -> Option<&PersistentNode<T>> {
+		path.iter().try_fold(&*self.root, |node, &position| node.child(position))
+	}
+	/// Returns `None` if `path` does not lead to a node
+	pub fn get(&self, path: &[usize]) -> Option<&T> {
+		self.node(path).map(PersistentNode::value)
+	}
+	/// Returns whether both heaps hold the very same root node, which is the case for a
+	/// snapshot that neither side has edited since
+	pub fn ptr_eq(&self, other: &Self) -> bool {
+		Arc::ptr_eq(&self.root, &other.root)
+	}
+}
+
+impl<T: Clone> PersistentHeap<T> {
+	// Copies every shared node along `path`, which must lead to a node
+	fn node_mut(&mut self, path: &[usize]) -> &mut PersistentNode<T> {
+		path.iter().fold(Arc::make_mut(&mut self.root), |node, &position| {
+			Arc::make_mut(&mut node.children[position])
+		})
+	}
+	/// Appends `value` as the last child of the node at `parent`.
+	/// Returns the position of the new child among its siblings.
+	/// Panics if `parent` does not lead to a node
+	pub fn insert(&mut self, value: T, parent: &[usize]) -> usize {
+		assert!(self.node(parent).is_some(), "Heap: Error: Tried to insert with invalid parent");
+		self.len += 1;
+		let node = self.node_mut(parent);
+		node.children.push(Arc::new(PersistentNode { value, children: Vec::new() }));
+		node.children.len() - 1
+	}
+	/// Removes the node at `path` along with all of its descendants.
+	/// Panics if `path` does not lead to a node.
+	/// Panics if `path` is empty.
+	pub fn remove(&mut self, path: &[usize]) {
+		let (&position, parent) = path.split_last().expect("Heap: Error: Tried to remove the root node.");
+		let removed = self.node(path).expect("Heap: Error: Tried to remove an invalid node").count();
+		self.node_mut(parent).children.remove(position);
+		self.len -= removed;
+	}
+	/// Returns the value the node held before.
+	/// Panics if `path` does not lead to a node
+	pub fn replace(&mut self, path: &[usize], value: T) -> T {
+		assert!(self.node(path).is_some(), "Heap: Error: Tried to replace the value of an invalid node");
+		core::mem::replace(&mut self.node_mut(path).value, value)
+	}
+
+	/// Copies the tree into a heap, numbering the nodes in depth-first pre-order
+	pub fn to_heap(&self) -> Heap<T> {
+		let mut heap = Heap::with_capacity(self.len, self.root.value.clone());
+		let mut stack: Vec<_> = self.root.children.iter().rev().map(|c| (c, 0)).collect();
+		while let Some((node, parent)) = stack.pop() {
+			let index = heap.insert(node.value.clone(), parent);
+			stack.extend(node.children.iter().rev().map(|c| (c, index)));
+		}
+		heap
+	}
+}
+
+impl<T: Clone> From<&Heap<T>> for PersistentHeap<T> {
+	fn from(heap: &Heap<T>) -> Self {
+		let mut built: Vec<Option<Arc<PersistentNode<T>>>> = Vec::new();
+		built.resize_with(heap.inner.len(), || None);
+		for (i, value) in heap.iter_post_order(0) {
+			let children = heap.children(i)
+				.map(|c| built[c].take().expect("Heap: Internal Error: Child visited before parent."))
+				.collect();
+			built[i] = Some(Arc::new(PersistentNode { value: value.clone(), children }));
+		}
+		let root = built[0].take().expect("Heap: Internal Error: Root was not visited.");
+		Self { root, len: heap.len() }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::sync::Arc;
+	use alloc::vec::Vec;
+
+	use super::PersistentHeap;
+	use crate::tree;
+
+	#[test]
+	fn snapshot_test() {
+		let heap = tree!("root" => ["a" => ["a1", "a2"], "b"]);
+		let mut current = PersistentHeap::from(&heap);
+		let snapshot = current.clone();
+		assert!(current.ptr_eq(&snapshot));
+		assert_eq!(current.insert("b1", &[1]), 0);
+		assert_eq!(current.replace(&[0, 1], "renamed"), "a2");
+		current.remove(&[0, 0]);
+		assert!(!current.ptr_eq(&snapshot));
+		assert_eq!((current.len(), snapshot.len()), (5, 5));
+		assert_eq!(current.get(&[0, 0]), Some(&"renamed"));
+		assert_eq!(snapshot.get(&[0, 0]), Some(&"a1"));
+		assert_eq!(snapshot.get(&[1, 0]), None);
+		assert_eq!(current.to_heap(), tree!("root" => ["a" => ["renamed"], "b" => ["b1"]]));
+		assert_eq!(snapshot.to_heap(), heap);
+	}
+
+	#[test]
+	fn structural_sharing_test() {
+		let mut current = PersistentHeap::new("root");
+		current.insert("a", &[]);
+		current.insert("b", &[]);
+		current.insert("a1", &[0]);
+		let snapshot = current.clone();
+		current.insert("b1", &[1]);
+		// Only the root and `b` were copied, so `a` is still shared
+		assert!(Arc::ptr_eq(&current.root.children[0], &snapshot.root.children[0]));
+		assert!(!Arc::ptr_eq(&current.root.children[1], &snapshot.root.children[1]));
+		let names: Vec<_> = current.root().children().map(|c| *c.value()).collect();
+		assert_eq!(names, ["a", "b"]);
+	}
+}
@@ -0,0 +1,148 @@
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use petgraph::graph::{Graph, IndexType, NodeIndex};
+use petgraph::Directed;
+
+use crate::Heap;
+
+/// Why a graph could not be converted into a heap. Nodes are given by their index in the graph.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphTreeError {
+	/// The graph has no nodes
+	Empty,
+	/// Every node has an incoming edge, so none can be the root
+	NoRoot,
+	/// Nodes `first` and `second` both have no incoming edges
+	MultipleRoots { first: usize, second: usize },
+	/// The node has more than one incoming edge
+	MultipleParents(usize),
+	/// The node cannot be reached from the root, because it lies on a cycle
+	Unreachable(usize),
+}
+
+impl fmt::Display for GraphTreeError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Empty => write!(f, "Heap: Error: The graph has no nodes"),
+			Self::NoRoot => write!(f, "Heap: Error: Every node of the graph has a parent"),
+			Self::MultipleRoots { first, second } =>
+				write!(f, "Heap: Error: Graph nodes {first} and {second} both have no parent"),
+			Self::MultipleParents(node) => write!(f, "Heap: Error: Graph node {node} has more than one parent"),
+			Self::Unreachable(node) => write!(f, "Heap: Error: Graph node {node} is not reachable from the root"),
+		}
+	}
+}
+
+impl core::error::Error for GraphTreeError {}
+
+/// Adds the nodes in depth-first pre-order, with an edge from every parent to each of its
+/// children added in sibling order
+impl<T: Clone> From<&Heap<T>> for Graph<T, ()> {
+	fn from(heap: &Heap<T>) -> Self {
+		let mut graph = Graph::with_capacity(heap.len(), heap.len() - 1);
+		let mut nodes = vec![NodeIndex::end(); heap.inner.len()];
+		for (i, value) in heap.iter_dfs(0) {
+			nodes[i] = graph.add_node(value.clone());
+			if let Some(parent) = heap.inner[i].parent() {
+				graph.add_edge(nodes[parent], nodes[i], ());
+			}
+		}
+		graph
+	}
+}
+
+/// Succeeds if the graph is a tree with its edges pointing away from the root. Edge weights
+/// are dropped, the nodes are numbered in depth-first pre-order, and the children of each
+/// node are ordered by the indices of the edges leading to them.
+impl<T: Clone, E, Ix: IndexType> TryFrom<&Graph<T, E, Directed, Ix>> for Heap<T> {
+	type Error = GraphTreeError;
+	fn try_from(graph: &Graph<T, E, Directed, Ix>) -> Result<Self, GraphTreeError> {
+		let mut children = vec![Vec::new(); graph.node_count()];
+		let mut has_parent = vec![false; graph.node_count()];
+		for edge in graph.raw_edges() {
+			let target = edge.target().index();
+			if core::mem::replace(&mut has_parent[target], true) {
+				return Err(GraphTreeError::MultipleParents(target));
+			}
+			children[edge.source().index()].push(edge.target());
+		}
+		let mut roots = (0..graph.node_count()).filter(|&n| !has_parent[n]);
+		let root = match (roots.next(), roots.next()) {
+			(Some(root), None) => root,
+			(Some(first), Some(second)) => return Err(GraphTreeError::MultipleRoots { first, second }),
+			(None, _) if graph.node_count() == 0 => return Err(GraphTreeError::Empty),
+			(None, _) => return Err(GraphTreeError::NoRoot),
+		};
+
+		let mut heap = Heap::with_capacity(graph.node_count(), graph[NodeIndex::new(root)].clone());
+		let mut stack: Vec<_> = children[root].iter().rev().map(|&c| (c, 0)).collect();
+		while let Some((node, parent)) = stack.pop() {
+			let index = heap.insert(graph[node].clone(), parent);
+			stack.extend(children[node.index()].iter().rev().map(|&c| (c, index)));
+		}
+		// With one root and at most one parent each, anything left over must be on a cycle
+		if heap.len() < graph.node_count() {
+			let mut reached = vec![false; graph.node_count()];
+			reached[root] = true;
+			let mut stack = vec![root];
+			while let Some(node) = stack.pop() {
+				for child in &children[node] {
+					reached[child.index()] = true;
+					stack.push(child.index());
+				}
+			}
+			let unreachable = reached.iter().position(|&r| !r).expect("Heap: Internal Error: No unreachable node.");
+			return Err(GraphTreeError::Unreachable(unreachable));
+		}
+		Ok(heap)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+
+	use petgraph::algo::dijkstra;
+	use petgraph::graph::{Graph, NodeIndex};
+
+	use super::GraphTreeError;
+	use crate::{tree, Heap};
+
+	#[test]
+	fn petgraph_round_trip_test() {
+		let mut heap = tree!("root" => ["a" => ["a1"], "b", "c"]);
+		heap.remove(2);
+		heap.insert("c1", 4);
+		let graph = Graph::from(&heap);
+		assert_eq!((graph.node_count(), graph.edge_count()), (5, 4));
+		let depths = dijkstra(&graph, NodeIndex::new(0), None, |_| 1);
+		let c1 = graph.node_indices().find(|&n| graph[n] == "c1").unwrap();
+		assert_eq!(depths[&c1], 2);
+		let back = Heap::try_from(&graph).unwrap();
+		let values: Vec<_> = back.iter_dfs(0).map(|(i, &v)| (v, back.parent_of(i).map(|p| back[p]))).collect();
+		assert_eq!(values, [("root", None), ("a", Some("root")), ("b", Some("root")), ("c", Some("root")), ("c1", Some("c"))]);
+	}
+
+	#[test]
+	fn petgraph_not_a_tree_test() {
+		let mut graph: Graph<&str, u8> = Graph::new();
+		assert_eq!(Heap::try_from(&graph), Err(GraphTreeError::Empty));
+		let (root, a, b) = (graph.add_node("root"), graph.add_node("a"), graph.add_node("b"));
+		assert_eq!(Heap::try_from(&graph), Err(GraphTreeError::MultipleRoots { first: 0, second: 1 }));
+		// Edges are followed in the order they were added, so `b` comes first
+		graph.add_edge(root, b, 1);
+		graph.add_edge(root, a, 2);
+		let heap = Heap::try_from(&graph).unwrap();
+		assert_eq!(heap.iter_dfs(0).map(|(_, &v)| v).collect::<Vec<_>>(), ["root", "b", "a"]);
+		graph.add_edge(a, b, 3);
+		assert_eq!(Heap::try_from(&graph), Err(GraphTreeError::MultipleParents(2)));
+		let mut cycle: Graph<(), ()> = Graph::new();
+		let (root, x, y) = (cycle.add_node(()), cycle.add_node(()), cycle.add_node(()));
+		cycle.extend_with_edges([(x, y), (y, x)]);
+		assert_eq!(Heap::try_from(&cycle), Err(GraphTreeError::Unreachable(1)));
+		cycle.add_edge(y, root, ());
+		assert_eq!(Heap::try_from(&cycle), Err(GraphTreeError::NoRoot));
+	}
+}
@@ -0,0 +1,73 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::{self, Display};
+
+use crate::Heap;
+
+/// Renders a heap like `tree(1)` output. Created by [`Heap::pretty`].
+pub struct Pretty<'a, T, F> {
+	heap: &'a Heap<T>,
+	format: F,
+}
+
+impl<T> Heap<T> {
+	/// Returns a `Display`able view of the heap drawn with box-drawing characters,
+	/// using `format` to render each value
+	pub fn pretty<F: Fn(&T) -> String>(&self, format: F) -> Pretty<'_, T, F> {
+		Pretty { heap: self, format }
+	}
+}
+
+impl<T, F: Fn(&T) -> String> Display for Pretty<'_, T, F> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		writeln!(f, "{}", (self.format)(&self.heap[0]))?;
+		// Each entry is a node along with the prefix inherited from its ancestors,
+		// and whether it is the last of its siblings
+		let mut stack: Vec<(usize, String, bool)> = Vec::new();
+		let push_children = |stack: &mut Vec<_>, index: usize, prefix: String| {
+			let mut children = self.heap.children(index).rev();
+			if let Some(last) = children.next() {
+				stack.push((last, prefix.clone(), true));
+			}
+			for child in children {
+				stack.push((child, prefix.clone(), false));
+			}
+		};
+		push_children(&mut stack, 0, String::new());
+		while let Some((index, prefix, last)) = stack.pop() {
+			let (branch, indent) = match last {
+				true => ("└── ", "    "),
+				false => ("├── ", "│   "),
+			};
+			writeln!(f, "{prefix}{branch}{}", (self.format)(&self.heap[index]))?;
+			push_children(&mut stack, index, prefix + indent);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Heap;
+
+	#[test]
+	fn pretty_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let b = heap.insert("b", 0);
+		let a1 = heap.insert("a1", a);
+		heap.insert("a2", a);
+		heap.insert("a1x", a1);
+		heap.insert("b1", b);
+		let rendered = heap.pretty(|v| v.to_uppercase()).to_string();
+		assert_eq!(rendered, "\
+ROOT
+├── A
+│   ├── A1
+│   │   └── A1X
+│   └── A2
+└── B
+    └── B1
+");
+	}
+}
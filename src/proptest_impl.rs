@@ -0,0 +1,114 @@
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use proptest::arbitrary::{any, any_with, Arbitrary};
+use proptest::collection::vec;
+use proptest::sample::Index;
+use proptest::strategy::{BoxedStrategy, Strategy};
+
+use crate::Heap;
+
+/// The shape of the heaps generated by [`heap_strategy`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeapParams {
+	/// How many nodes to generate besides the root. Fewer are generated if `max_depth` and
+	/// `max_children` leave no room for more.
+	pub descendants: Range<usize>,
+	/// The root is at depth 0, so 0 only ever generates the root
+	pub max_depth: usize,
+	pub max_children: usize,
+}
+
+impl Default for HeapParams {
+	fn default() -> Self {
+		Self { descendants: 0..32, max_depth: usize::MAX, max_children: usize::MAX }
+	}
+}
+
+/// Generates heaps whose values come from `value` and whose shape follows `params`.
+/// Each node after the root picks its parent among the nodes generated before it that still
+/// have room for a child, so the nodes are numbered in insertion order without gaps.
+/// Heaps shrink by losing nodes and by moving nodes towards the root.
+pub fn heap_strategy<S: Strategy>(value: S, params: HeapParams) -> impl Strategy<Value = Heap<S::Value>>
+{
+	// Shared between the root and the other nodes, so `value` need not be `Clone`
+	let value = Arc::new(value);
+	let nodes = vec((value.clone(), any::<Index>()), params.descendants.clone());
+	(value, nodes).prop_map(move |(root, nodes)| build(root, nodes, &params))
+}
+
+fn build<T>(root: T, nodes: Vec<(T, Index)>, params: &HeapParams) -> Heap<T> {
+	let mut heap = Heap::with_capacity(nodes.len() + 1, root);
+	// Nodes that may still get a child, in the order they were inserted
+	let mut open = Vec::new();
+	if params.max_depth > 0 && params.max_children > 0 {
+		open.push(0);
+	}
+	for (value, parent) in nodes {
+		if open.is_empty() {
+			break;
+		}
+		let position = parent.index(open.len());
+		let parent = open[position];
+		let index = heap.insert(value, parent);
+		if heap.child_count(parent) == params.max_children {
+			open.remove(position);
+		}
+		if heap.depth_of(index) < params.max_depth {
+			open.push(index);
+		}
+	}
+	heap
+}
+
+impl<T: Arbitrary + 'static> Arbitrary for Heap<T> {
+	type Parameters = (HeapParams, T::Parameters);
+	type Strategy = BoxedStrategy<Self>;
+	fn arbitrary_with((params, args): Self::Parameters) -> Self::Strategy {
+		heap_strategy(any_with::<T>(args), params).boxed()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use proptest::prelude::*;
+
+	use super::{heap_strategy, HeapParams};
+	use crate::Heap;
+
+	proptest! {
+		#[test]
+		fn heap_strategy_test(heap in heap_strategy(0..10u8, HeapParams {
+			descendants: 0..50,
+			max_depth: 3,
+			max_children: 2,
+		})) {
+			prop_assert!(heap.len() <= 15);
+			for (i, _) in heap.iter_dfs(0) {
+				prop_assert!(heap.depth_of(i) <= 3);
+				prop_assert!(heap.child_count(i) <= 2);
+			}
+		}
+
+		#[test]
+		fn arbitrary_heap_test(heap in any::<Heap<u8>>()) {
+			prop_assert!(heap.len() <= 32);
+			prop_assert_eq!(heap.iter().count(), heap.len());
+		}
+	}
+
+	#[test]
+	fn shrinks_towards_root_test() {
+		use proptest::strategy::ValueTree;
+		use proptest::test_runner::TestRunner;
+
+		let mut runner = TestRunner::deterministic();
+		let strategy = heap_strategy(Just(()), HeapParams { descendants: 5..6, ..HeapParams::default() });
+		let mut tree = strategy.new_tree(&mut runner).unwrap();
+		while tree.simplify() {}
+		let heap = tree.current();
+		assert_eq!(heap.len(), 6);
+		assert!(heap.iter_dfs(0).all(|(i, _)| heap.depth_of(i) <= 1));
+	}
+}
@@ -0,0 +1,229 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use rkyv::api::high::{HighDeserializer, HighSerializer, HighValidator};
+use rkyv::bytecheck::CheckBytes;
+use rkyv::rancor::{Error, Source};
+use rkyv::ser::allocator::ArenaHandle;
+use rkyv::util::AlignedVec;
+use rkyv::with::{Inline, Map};
+use rkyv::{Archive, Archived, Deserialize, Serialize};
+
+use crate::Heap;
+
+// The nodes in depth-first pre-order, along with the size of each node's subtree and the
+// position of its parent, which is all a reader needs to walk the tree in place
+#[derive(Archive, Serialize)]
+struct Layout<'a, T> {
+	#[rkyv(with = Map<Inline>)]
+	values: Vec<&'a T>,
+	sizes: Vec<u64>,
+	parents: Vec<u64>,
+}
+
+/// A read-only view of a heap serialized with [`Heap::to_rkyv`], used in place without
+/// deserializing, for example straight from a memory-mapped file. Nodes are numbered in
+/// depth-first pre-order, so the root is 0 and every subtree is a consecutive range.
+pub struct ArchivedHeap<'a, T: Archive> {
+	layout: &'a ArchivedLayout<'a, T>,
+}
+
+impl<T: Archive> Clone for ArchivedHeap<'_, T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T: Archive> Copy for ArchivedHeap<'_, T> {}
+
+impl<T> Heap<T> {
+	/// Serializes the heap so that it can be read back without deserializing through
+	/// [`ArchivedHeap`]. Removed slots are left out and the nodes are renumbered in depth-first
+	/// pre-order, so the indices match those of `reorder_dfs`.
+	pub fn to_rkyv(&self) -> Result<AlignedVec, Error>
+	where
+		T: for<'a> Serialize<HighSerializer<AlignedVec, ArenaHandle<'a>, Error>>,
+	{
+		let mut position = vec![0; self.inner.len()];
+		let mut layout = Layout {
+			values: Vec::with_capacity(self.len()),
+			sizes: Vec::with_capacity(self.len()),
+			parents: Vec::with_capacity(self.len()),
+		};
+		for (i, value) in self.iter_dfs(0) {
+			position[i] = layout.values.len() as u64;
+			layout.values.push(value);
+			layout.sizes.push(self.inner[i].size as u64);
+			layout.parents.push(self.inner[i].parent().map_or(0, |p| position[p]));
+		}
+		rkyv::to_bytes(&layout)
+	}
+}
+
+impl<'a, T: Archive> ArchivedHeap<'a, T>
+where
+	T::Archived: for<'b> CheckBytes<HighValidator<'b, Error>>,
+{
+	/// Checks that `bytes` hold a heap written by `Heap::to_rkyv`. `bytes` must be aligned as
+	/// `AlignedVec` aligns them.
+	pub fn access(bytes: &'a [u8]) -> Result<Self, Error> {
+		let layout = rkyv::access::<ArchivedLayout<'a, T>, Error>(bytes)?;
+		// Traversal indexes by the sizes and parents, so they must describe a real tree
+		match is_tree(&layout.sizes, &layout.parents, layout.values.len()) {
+			true => Ok(Self { layout }),
+			false => Err(Error::new(InvalidLayout)),
+		}
+	}
+}
+
+fn is_tree(sizes: &[Archived<u64>], parents: &[Archived<u64>], len: usize) -> bool {
+	// Archived values may not fit in a `usize` on this target, and are rejected if so
+	let native = |value: &Archived<u64>| usize::try_from(value.to_native()).ok();
+	if sizes.len() != len || parents.len() != len || len == 0 || native(&sizes[0]) != Some(len) {
+		return false;
+	}
+	// The subtrees containing the current node, as (index, end)
+	let mut open = vec![(0, len)];
+	for (i, (size, parent_of)) in sizes.iter().zip(parents).enumerate().skip(1) {
+		while open.last().is_some_and(|&(_, end)| end <= i) {
+			open.pop();
+		}
+		let &(parent, end) = open.last().expect("Heap: Internal Error: Left the root's subtree.");
+		let subtree_end = match native(size) {
+			Some(size) if size > 0 => i.checked_add(size),
+			_ => None,
+		};
+		match (native(parent_of), subtree_end) {
+			(Some(parent_of), Some(subtree_end)) if parent_of == parent && subtree_end <= end => {
+				open.push((i, subtree_end));
+			}
+			_ => return false,
+		}
+	}
+	true
+}
+
+#[derive(Debug)]
+struct InvalidLayout;
+
+impl core::fmt::Display for InvalidLayout {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		write!(f, "Heap: Error: The archived heap is not a valid tree")
+	}
+}
+
+impl core::error::Error for InvalidLayout {}
+
+// `access` checked that every size and parent fits in a `usize` and stays within the
+// archive, so the casts below lose nothing
+impl<'a, T: Archive> ArchivedHeap<'a, T> {
+	// An archived heap always contains its root, so it can never be empty
+	#[allow(clippy::len_without_is_empty)]
+	pub fn len(&self) -> usize {
+		self.layout.values.len()
+	}
+	/// Returns `None` if `index` is out of bounds
+	pub fn get(&self, index: usize) -> Option<&'a T::Archived> {
+		self.layout.values.get(index)
+	}
+	/// Returns `None` for the root, or if `index` is out of bounds
+	pub fn parent_of(&self, index: usize) -> Option<usize> {
+		match index {
+			0 => None,
+			_ => self.layout.parents.get(index).map(|p| p.to_native() as usize),
+		}
+	}
+	/// The number of nodes in the subtree of `index`, including itself.
+	/// Panics if `index` is out of bounds
+	pub fn subtree_size(&self, index: usize) -> usize {
+		self.layout.sizes[index].to_native() as usize
+	}
+	/// Lazily yields the children of `index` in sibling order. Yields nothing if `index` is
+	/// out of bounds.
+	pub fn children(&self, index: usize) -> impl Iterator<Item = usize> + 'a {
+		let sizes = &self.layout.sizes;
+		let end = sizes.get(index).map_or(0, |size| index + size.to_native() as usize);
+		let mut next = index + 1;
+		core::iter::from_fn(move || {
+			let child = next;
+			(child < end).then(|| {
+				next += sizes[child].to_native() as usize;
+				child
+			})
+		})
+	}
+	/// Yields every node and its value in depth-first pre-order, which is index order
+	pub fn iter(&self) -> impl Iterator<Item = (usize, &'a T::Archived)> + 'a {
+		self.layout.values.iter().enumerate()
+	}
+
+	/// Deserializes the archive back into a heap, numbered in depth-first pre-order
+	pub fn to_heap(&self) -> Result<Heap<T>, Error>
+	where
+		T::Archived: Deserialize<T, HighDeserializer<Error>>,
+	{
+		let mut values = self.layout.values.iter();
+		let root = values.next().expect("Heap: Internal Error: Archived heap has no root.");
+		let mut heap = Heap::with_capacity(self.len(), rkyv::deserialize(root)?);
+		for (i, value) in values.enumerate() {
+			let parent = self.layout.parents[i + 1].to_native() as usize;
+			heap.insert(rkyv::deserialize(value)?, parent);
+		}
+		Ok(heap)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+	use alloc::vec::Vec;
+
+	use rkyv::Archived;
+
+	use super::{is_tree, ArchivedHeap};
+	use crate::{tree, Heap};
+
+	#[test]
+	fn rkyv_round_trip_test() {
+		let mut heap = tree!(String::from("root") => [
+			String::from("a") => [String::from("a1")],
+			String::from("b"),
+			String::from("c") => [String::from("c1"), String::from("c2")],
+		]);
+		heap.remove(3);
+		heap.move_child(4, 0);
+		let bytes = heap.to_rkyv().unwrap();
+		let archived = ArchivedHeap::<String>::access(&bytes).unwrap();
+		assert_eq!(archived.len(), 6);
+		let values: Vec<_> = archived.iter().map(|(_, v)| v.as_str()).collect();
+		assert_eq!(values, ["root", "c", "c1", "c2", "a", "a1"]);
+		assert_eq!(archived.children(0).collect::<Vec<_>>(), [1, 4]);
+		assert_eq!(archived.children(1).collect::<Vec<_>>(), [2, 3]);
+		assert_eq!(archived.children(9).count(), 0);
+		assert_eq!((archived.parent_of(0), archived.parent_of(5)), (None, Some(4)));
+		assert_eq!(archived.subtree_size(1), 3);
+		assert_eq!(archived.get(4).map(|v| v.as_str()), Some("a"));
+
+		let mut expected = heap.clone();
+		expected.reorder_dfs(|_, _| {});
+		assert_eq!(archived.to_heap().unwrap(), expected);
+		assert!(ArchivedHeap::<String>::access(&bytes[..bytes.len() - 4]).is_err());
+		assert_eq!(ArchivedHeap::<u8>::access(&Heap::new(7u8).to_rkyv().unwrap()).unwrap().len(), 1);
+	}
+
+	#[test]
+	fn is_tree_test() {
+		let archive = |values: &[u64]| values.iter().map(|&v| Archived::<u64>::from_native(v)).collect::<Vec<_>>();
+		// root => [a => [a1], b]
+		assert!(is_tree(&archive(&[4, 2, 1, 1]), &archive(&[0, 0, 1, 0]), 4));
+		assert!(!is_tree(&archive(&[4, 2, 1, 1]), &archive(&[0, 0, 0, 0]), 4));
+		assert!(is_tree(&archive(&[4, 3, 1, 1]), &archive(&[0, 0, 1, 1]), 4));
+		assert!(!is_tree(&archive(&[4, 3, 1, 1]), &archive(&[0, 0, 1, 0]), 4));
+		assert!(!is_tree(&archive(&[4, 2, 0, 1]), &archive(&[0, 0, 1, 0]), 4));
+		assert!(!is_tree(&archive(&[3, 2, 1, 1]), &archive(&[0, 0, 1, 0]), 4));
+		assert!(!is_tree(&[], &[], 0));
+		assert!(!is_tree(&archive(&[4, u64::MAX, 1, 1]), &archive(&[0, 0, 1, 0]), 4));
+		assert!(!is_tree(&archive(&[u64::MAX, 2, 1, 1]), &archive(&[0, 0, 1, 0]), 4));
+		assert!(!is_tree(&archive(&[4, 2, 1, 1]), &archive(&[0, 0, u64::MAX, 0]), 4));
+	}
+}
@@ -0,0 +1,79 @@
+use alloc::format;
+use alloc::vec;
+use alloc::vec::Vec;
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::Heap;
+
+// A heap is serialized as a sequence of `(value, parent)` pairs in depth-first pre-order,
+// where `parent` is the position of the parent within the sequence.
+// The root comes first and is the only entry without a parent.
+
+impl<T: Serialize> Serialize for Heap<T> {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let mut positions = vec![0; self.inner.len()];
+		let mut seq = serializer.serialize_seq(Some(self.len()))?;
+		for (position, (i, value)) in self.iter_dfs(0).enumerate() {
+			positions[i] = position;
+			let parent = self.inner[i].parent().map(|p| positions[p]);
+			seq.serialize_element(&(value, parent))?;
+		}
+		seq.end()
+	}
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Heap<T> {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let nodes = Vec::<(T, Option<usize>)>::deserialize(deserializer)?;
+		// Requiring every parent to precede its children rules out both cycles and dangling parents
+		for (position, (_, parent)) in nodes.iter().enumerate() {
+			match (position, parent) {
+				(0, None) => {}
+				(0, Some(_)) => return Err(D::Error::custom("the root node cannot have a parent")),
+				(_, None) => return Err(D::Error::custom(format!("node {position} has no parent"))),
+				(_, Some(p)) if *p >= position => return Err(D::Error::custom(
+					format!("node {position} has parent {p}, which does not precede it")
+				)),
+				_ => {}
+			}
+		}
+		let mut nodes = nodes.into_iter();
+		let (root, _) = nodes.next().ok_or_else(|| D::Error::custom("missing root node"))?;
+		let mut heap = Heap::with_capacity(nodes.len() + 1, root);
+		for (value, parent) in nodes {
+			heap.insert(value, parent.expect("validated above"));
+		}
+		Ok(heap)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Heap;
+
+	#[test]
+	fn round_trip_test() {
+		let mut heap = Heap::new(String::from("root"));
+		let a = heap.insert(String::from("a"), 0);
+		let b = heap.insert(String::from("b"), 0);
+		heap.insert(String::from("a1"), a);
+		heap.remove(b);
+		heap.insert(String::from("c"), 0);
+		let json = serde_json::to_string(&heap).unwrap();
+		assert_eq!(json, r#"[["root",null],["a",0],["a1",1],["c",0]]"#);
+		let back: Heap<String> = serde_json::from_str(&json).unwrap();
+		assert_eq!(back.len(), 4);
+		assert_eq!(serde_json::to_string(&back).unwrap(), json);
+	}
+
+	#[test]
+	fn invalid_parents_test() {
+		assert!(serde_json::from_str::<Heap<u8>>("[]").is_err());
+		assert!(serde_json::from_str::<Heap<u8>>("[[0,0]]").is_err());
+		assert!(serde_json::from_str::<Heap<u8>>("[[0,null],[1,null]]").is_err());
+		assert!(serde_json::from_str::<Heap<u8>>("[[0,null],[1,2],[2,1]]").is_err());
+		assert!(serde_json::from_str::<Heap<u8>>("[[0,null],[1,7]]").is_err());
+		assert!(serde_json::from_str::<Heap<u8>>("[[0,null],[1,0],[2,1]]").is_ok());
+	}
+}
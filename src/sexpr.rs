@@ -0,0 +1,236 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt::{self, Display, Write};
+use core::ops::ControlFlow;
+use core::str::FromStr;
+
+use crate::{Heap, Visitor, WalkControl};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SexprError<E> {
+	/// The input held `found` at byte `position`, or ended there if `found` is `None`
+	Unexpected { position: usize, found: Option<char> },
+	/// The atom starting at byte `position` could not be parsed as a value
+	InvalidAtom { position: usize, error: E },
+}
+
+impl<E: Display> fmt::Display for SexprError<E> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unexpected { position, found: Some(c) } =>
+				write!(f, "Heap: Error: Unexpected {c:?} at byte {position} of the S-expression"),
+			Self::Unexpected { position, found: None } =>
+				write!(f, "Heap: Error: The S-expression ended early at byte {position}"),
+			Self::InvalidAtom { position, error } =>
+				write!(f, "Heap: Error: Invalid atom at byte {position} of the S-expression: {error}"),
+		}
+	}
+}
+
+impl<E: fmt::Debug + Display> core::error::Error for SexprError<E> {}
+
+// Characters that end an unquoted atom
+const RESERVED: &str = "()\";\\";
+
+struct Writer<'a, T, W> {
+	heap: &'a Heap<T>,
+	out: &'a mut W,
+}
+
+impl<T: Display, W: Write> Visitor<T> for Writer<'_, T, W> {
+	type Break = fmt::Error;
+	fn enter(&mut self, index: usize, value: &T) -> WalkControl<fmt::Error> {
+		match self.open(index, value) {
+			Ok(()) => WalkControl::Continue,
+			Err(e) => WalkControl::Break(e),
+		}
+	}
+	fn leave(&mut self, index: usize, _: &T) -> ControlFlow<fmt::Error> {
+		match !self.heap.is_leaf(index) && self.out.write_char(')').is_err() {
+			true => ControlFlow::Break(fmt::Error),
+			false => ControlFlow::Continue(()),
+		}
+	}
+}
+
+impl<T: Display, W: Write> Writer<'_, T, W> {
+	// Every node but the root follows its parent's atom or a sibling's subtree
+	fn open(&mut self, index: usize, value: &T) -> fmt::Result {
+		if index != 0 {
+			self.out.write_char(' ')?;
+		}
+		if !self.heap.is_leaf(index) {
+			self.out.write_char('(')?;
+		}
+		write_atom(self.out, &value.to_string())
+	}
+}
+
+fn write_atom<W: Write>(out: &mut W, atom: &str) -> fmt::Result {
+	if !atom.is_empty() && !atom.chars().any(|c| c.is_whitespace() || RESERVED.contains(c)) {
+		return out.write_str(atom);
+	}
+	out.write_char('"')?;
+	for c in atom.chars() {
+		if c == '"' || c == '\\' {
+			out.write_char('\\')?;
+		}
+		out.write_char(c)?;
+	}
+	out.write_char('"')
+}
+
+struct Parser<'a> {
+	input: &'a str,
+	position: usize,
+}
+
+impl Parser<'_> {
+	fn peek(&self) -> Option<char> {
+		self.input[self.position..].chars().next()
+	}
+	fn bump(&mut self) -> Option<char> {
+		let c = self.peek()?;
+		self.position += c.len_utf8();
+		Some(c)
+	}
+	fn unexpected<E>(&self) -> SexprError<E> {
+		SexprError::Unexpected { position: self.position, found: self.peek() }
+	}
+	// Skips whitespace and comments running from `;` to the end of the line
+	fn skip_space(&mut self) {
+		while let Some(c) = self.peek() {
+			match c {
+				';' => while self.bump().is_some_and(|c| c != '\n') {},
+				c if c.is_whitespace() => {
+					self.bump();
+				}
+				_ => return,
+			}
+		}
+	}
+	fn atom<T: FromStr>(&mut self) -> Result<T, SexprError<T::Err>> {
+		self.skip_space();
+		let position = self.position;
+		let mut atom = String::new();
+		if self.peek() == Some('"') {
+			self.bump();
+			loop {
+				match self.bump().ok_or_else(|| self.unexpected())? {
+					'"' => break,
+					'\\' => atom.push(self.bump().ok_or_else(|| self.unexpected())?),
+					c => atom.push(c),
+				}
+			}
+		} else {
+			while let Some(c) = self.peek().filter(|&c| !c.is_whitespace() && !RESERVED.contains(c)) {
+				atom.push(c);
+				self.bump();
+			}
+			if atom.is_empty() {
+				return Err(self.unexpected());
+			}
+		}
+		atom.parse().map_err(|error| SexprError::InvalidAtom { position, error })
+	}
+}
+
+impl<T: Display> Heap<T> {
+	/// Renders the heap as an S-expression. A leaf is written as its value's atom, and any
+	/// other node as a list of its atom followed by its children, so `(root (a a1) b)`.
+	/// Atoms are quoted with `"` when they are empty or contain whitespace, parentheses,
+	/// quotes, backslashes or `;`.
+	pub fn to_sexpr(&self) -> String {
+		let mut out = String::new();
+		self.write_sexpr(&mut out).expect("Heap: Internal Error: Writing to a String failed.");
+		out
+	}
+	pub fn write_sexpr<W: Write>(&self, out: &mut W) -> fmt::Result {
+		match self.walk(0, &mut Writer { heap: self, out }) {
+			Some(e) => Err(e),
+			None => Ok(()),
+		}
+	}
+}
+
+impl<T: FromStr> Heap<T> {
+	/// Parses an S-expression in the form `to_sexpr` writes, numbering its nodes in
+	/// depth-first pre-order. A list must start with an atom, so `()` is rejected, and
+	/// `(a)` is read as the leaf `a`. Comments run from `;` to the end of the line.
+	pub fn from_sexpr(input: &str) -> Result<Self, SexprError<T::Err>> {
+		let mut parser = Parser { input, position: 0 };
+		let mut heap: Option<Heap<T>> = None;
+		// The lists that are still being read
+		let mut open: Vec<usize> = Vec::new();
+		loop {
+			parser.skip_space();
+			let parent = open.last().copied();
+			if heap.is_some() && parent.is_none() {
+				break;
+			}
+			let list = match parser.peek() {
+				Some(')') if parent.is_some() => {
+					parser.bump();
+					open.pop();
+					continue;
+				}
+				Some('(') => {
+					parser.bump();
+					true
+				}
+				_ => false,
+			};
+			let value = parser.atom()?;
+			let index = match (&mut heap, parent) {
+				(Some(heap), Some(parent)) => heap.insert(value, parent),
+				(heap, _) => {
+					*heap = Some(Heap::new(value));
+					0
+				}
+			};
+			if list {
+				open.push(index);
+			}
+		}
+		match parser.peek() {
+			None => Ok(heap.expect("Heap: Internal Error: No root was read.")),
+			Some(_) => Err(parser.unexpected()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+
+	use super::SexprError;
+	use crate::{tree, Heap};
+
+	#[test]
+	fn sexpr_round_trip_test() {
+		let heap = tree!(String::from("define") => [
+			String::from("f") => [String::from("x")],
+			String::from("say \"hi\""),
+			String::new(),
+		]);
+		let text = heap.to_sexpr();
+		assert_eq!(text, r#"(define (f x) "say \"hi\"" "")"#);
+		assert_eq!(Heap::<String>::from_sexpr(&text).unwrap(), heap);
+		let numbers = tree!(1 => [2 => [3, 4], 5]);
+		assert_eq!(numbers.to_sexpr(), "(1 (2 3 4) 5)");
+		let spaced = Heap::<u32>::from_sexpr(" ; numbers\n(1\n  (2 3 4) ; the first child\n  (5))\n").unwrap();
+		assert_eq!(spaced, numbers);
+		assert_eq!(Heap::<u32>::from_sexpr("7").unwrap().to_sexpr(), "7");
+	}
+
+	#[test]
+	fn sexpr_errors_test() {
+		let unexpected = |position, found| Err(SexprError::Unexpected { position, found });
+		assert_eq!(Heap::<String>::from_sexpr("(a (b c)"), unexpected(8, None));
+		assert_eq!(Heap::<String>::from_sexpr("()"), unexpected(1, Some(')')));
+		assert_eq!(Heap::<String>::from_sexpr("a b"), unexpected(2, Some('b')));
+		assert_eq!(Heap::<String>::from_sexpr("(a \"b)"), unexpected(6, None));
+		let invalid = Heap::<u32>::from_sexpr("(1 x)");
+		assert!(matches!(invalid, Err(SexprError::InvalidAtom { position: 3, .. })));
+	}
+}
@@ -0,0 +1,89 @@
+use slotmap::{new_key_type, Key, KeyData};
+
+use crate::Heap;
+
+new_key_type! {
+	/// A `slotmap` key for a node, so per-node data can be kept in a `SecondaryMap` or any
+	/// other container keyed by `slotmap` keys. Like a [`NodeId`](crate::NodeId), it stops
+	/// resolving once its node is removed. Created by [`Heap::key_of`].
+	/// Keys only hold the low 31 bits of a node's generation, and only exist for the first
+	/// `u32::MAX` slots.
+	pub struct NodeKey;
+}
+
+// Slotmap versions must be odd, which costs the generation its top bit
+fn version(generation: u32) -> u32 {
+	(generation << 1) | 1
+}
+
+impl<T> Heap<T> {
+	/// Returns a key for the node at `index`, or `None` if `index` is invalid or too large
+	/// to fit in a key
+	pub fn key_of(&self, index: usize) -> Option<NodeKey> {
+		let idx = u32::try_from(index).ok().filter(|&idx| idx != u32::MAX)?;
+		let id = self.id_of(index)?;
+		let ffi = (u64::from(version(id.generation())) << 32) | u64::from(idx);
+		Some(KeyData::from_ffi(ffi).into())
+	}
+	/// Returns the current index of the node `key` refers to.
+	/// Returns `None` if that node has since been removed, or `key` is null.
+	pub fn resolve_key(&self, key: NodeKey) -> Option<usize> {
+		let ffi = key.data().as_ffi();
+		let (index, key_version) = (ffi as u32 as usize, (ffi >> 32) as u32);
+		match !key.is_null() && self.is_valid_idx(index) && version(self.inner[index].generation) == key_version {
+			true => Some(index),
+			false => None,
+		}
+	}
+	pub fn contains_key(&self, key: NodeKey) -> bool {
+		self.resolve_key(key).is_some()
+	}
+	pub fn get_by_key(&self, key: NodeKey) -> Option<&T> {
+		self.resolve_key(key).and_then(|i| self.get(i))
+	}
+	pub fn get_mut_by_key(&mut self, key: NodeKey) -> Option<&mut T> {
+		self.resolve_key(key).and_then(|i| self.get_mut(i))
+	}
+	/// Panics if `parent` does not refer to a valid node.
+	/// Panics if the new node's index is too large to fit in a key.
+	pub fn insert_key(&mut self, node: T, parent: NodeKey) -> NodeKey {
+		let parent = self.resolve_key(parent)
+			.expect("Heap: Error: Tried to insert with invalid parent");
+		let index = self.insert(node, parent);
+		self.key_of(index).expect("Heap: Error: Ran out of indices")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use slotmap::{Key, SecondaryMap};
+
+	use super::NodeKey;
+	use crate::Heap;
+
+	#[test]
+	fn node_key_test() {
+		let mut heap = Heap::new("root");
+		let root = heap.key_of(0).unwrap();
+		let a = heap.insert_key("a", root);
+		let b = heap.insert_key("b", a);
+		let mut sizes = SecondaryMap::new();
+		sizes.insert(a, 2);
+		sizes.insert(b, 1);
+		assert_eq!((heap.get_by_key(b), sizes[a]), (Some(&"b"), 2));
+
+		heap.remove(heap.resolve_key(b).unwrap());
+		let c = heap.insert_key("c", root);
+		// `c` reuses the slot of `b`, but neither key answers for the other's node
+		assert_eq!(heap.resolve_key(c), heap.resolve_key(a).map(|a| a + 1));
+		assert_ne!(b, c);
+		assert!(!heap.contains_key(b));
+		assert_eq!(sizes.get(c), None);
+		sizes.insert(c, 5);
+		assert_eq!((sizes.get(b), sizes.len()), (None, 2));
+		*heap.get_mut_by_key(c).unwrap() = "renamed";
+		assert_eq!(heap[2], "renamed");
+		assert_eq!(heap.resolve_key(NodeKey::null()), None);
+		assert_eq!(heap.key_of(7), None);
+	}
+}
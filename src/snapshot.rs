@@ -0,0 +1,70 @@
+use alloc::sync::Arc;
+use core::ops::Deref;
+
+use crate::Heap;
+
+/// An immutable copy of a heap that can be cloned cheaply and shared between threads.
+/// It holds exactly the nodes, values and indices the heap had when it was frozen; later
+/// edits to the heap are not visible through it. Dereferences to the frozen `Heap`.
+#[derive(Debug)]
+pub struct HeapSnapshot<T>(Arc<Heap<T>>);
+
+// Derived impls would needlessly require `T: Clone`
+impl<T> Clone for HeapSnapshot<T> {
+	fn clone(&self) -> Self {
+		Self(self.0.clone())
+	}
+}
+
+impl<T> HeapSnapshot<T> {
+	/// Returns whether both snapshots share the same frozen heap
+	pub fn ptr_eq(&self, other: &Self) -> bool {
+		Arc::ptr_eq(&self.0, &other.0)
+	}
+}
+
+impl<T> Deref for HeapSnapshot<T> {
+	type Target = Heap<T>;
+
+	fn deref(&self) -> &Heap<T> {
+		&self.0
+	}
+}
+
+/// Freezes the heap without copying it
+impl<T> From<Heap<T>> for HeapSnapshot<T> {
+	fn from(heap: Heap<T>) -> Self {
+		Self(Arc::new(heap))
+	}
+}
+
+impl<T: Clone> Heap<T> {
+	/// Copies the heap into a snapshot, leaving the heap free to be mutated.
+	/// The snapshot has no observer and does not update any `WeakNodeId`.
+	pub fn freeze(&self) -> HeapSnapshot<T> {
+		HeapSnapshot::from(self.clone())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec::Vec;
+	use std::thread;
+
+	use crate::tree;
+
+	#[test]
+	fn freeze_test() {
+		let mut heap = tree!("root" => ["a" => ["a1"], "b"]);
+		let snapshot = heap.freeze();
+		let shared = snapshot.clone();
+		assert!(snapshot.ptr_eq(&shared));
+		let reader = thread::spawn(move || shared.iter_dfs(0).map(|(_, v)| *v).collect::<Vec<_>>());
+		heap.remove(1);
+		heap.insert("c", 0);
+		assert_eq!(reader.join().unwrap(), ["root", "a", "a1", "b"]);
+		assert_eq!(snapshot.len(), 4);
+		assert_eq!(snapshot[1], "a");
+		assert_eq!(heap[1], "c");
+	}
+}
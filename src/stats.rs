@@ -0,0 +1,85 @@
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::Heap;
+
+/// Shape metrics of a heap. Created by [`Heap::stats`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct HeapStats {
+	pub nodes: usize,
+	/// The depth of the deepest node, so a heap holding only its root has height 0
+	pub height: usize,
+	pub leaves: usize,
+	/// The most children any single node has
+	pub max_branching: usize,
+	/// The mean number of children among nodes that have any, or 0 if only the root exists
+	pub average_branching: f64,
+	/// The number of nodes at each depth, starting with the root's level
+	pub level_widths: Vec<usize>,
+}
+
+impl<T> Heap<T> {
+	/// Gathers shape metrics in a single pass over the heap
+	pub fn stats(&self) -> HeapStats {
+		let mut level_widths = vec![0];
+		let (mut leaves, mut max_branching) = (0, 0);
+		for (i, _) in self.iter_dfs(0) {
+			let depth = self.depth_of(i);
+			if depth == level_widths.len() {
+				level_widths.push(0);
+			}
+			level_widths[depth] += 1;
+			let children = self.child_count(i);
+			if children == 0 {
+				leaves += 1;
+			}
+			max_branching = max_branching.max(children);
+		}
+		let internal = self.len() - leaves;
+		HeapStats {
+			nodes: self.len(),
+			height: level_widths.len() - 1,
+			leaves,
+			max_branching,
+			average_branching: match internal {
+				0 => 0.0,
+				_ => (self.len() - 1) as f64 / internal as f64,
+			},
+			level_widths,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::vec;
+
+	use crate::{Heap, HeapStats};
+
+	#[test]
+	fn stats_test() {
+		let mut heap = Heap::new("root");
+		assert_eq!(heap.stats(), HeapStats {
+			nodes: 1,
+			height: 0,
+			leaves: 1,
+			max_branching: 0,
+			average_branching: 0.0,
+			level_widths: vec![1],
+		});
+		let a = heap.insert("a", 0);
+		heap.insert("b", 0);
+		heap.insert("c", 0);
+		let a1 = heap.insert("a1", a);
+		heap.insert("a2", a);
+		heap.insert("a1x", a1);
+		assert_eq!(heap.stats(), HeapStats {
+			nodes: 7,
+			height: 3,
+			leaves: 4,
+			max_branching: 3,
+			average_branching: 2.0,
+			level_widths: vec![1, 3, 2, 1],
+		});
+	}
+}
@@ -0,0 +1,154 @@
+use alloc::vec::Vec;
+
+use crate::observer::Observer;
+use crate::weak::Watchers;
+use crate::Heap;
+
+impl<T> Heap<T> {
+	/// Builds a heap with the same nodes at the same indices, holding the results of
+	/// calling `f` on each value. Every `NodeId`, `WeakNodeId` and the observer carry over.
+	pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> Heap<U> {
+		let mut inner = self.sibling_nodes(self.inner.len());
+		inner.extend(self.inner.into_iter().map(|mut node| {
+			let value = node.value.take().map(&mut f);
+			node.with_value(value)
+		}));
+		Heap {
+			inner,
+			free: self.free,
+			len: self.len,
+			min_generation: self.min_generation,
+			watchers: self.watchers,
+			observer: self.observer,
+			pre_order: self.pre_order,
+		}
+	}
+	/// Like `map`, but stops at the first value for which `f` fails and returns its error.
+	/// Values are visited in index order, and are all dropped on failure.
+	pub fn try_map<U, E, F: FnMut(T) -> Result<U, E>>(self, mut f: F) -> Result<Heap<U>, E> {
+		let mut inner = self.sibling_nodes(self.inner.len());
+		for mut node in self.inner {
+			let value = node.value.take().map(&mut f).transpose()?;
+			inner.push(node.with_value(value));
+		}
+		Ok(Heap {
+			inner,
+			free: self.free,
+			len: self.len,
+			min_generation: self.min_generation,
+			watchers: self.watchers,
+			observer: self.observer,
+			pre_order: self.pre_order,
+		})
+	}
+	/// Reduces the subtree rooted at `start` bottom-up: `f` gets each node's value along with
+	/// the results of its children, in the same order as `children`, and its result is passed
+	/// on to the node's parent. Returns the result for `start`.
+	/// Panics if `start` is invalid
+	pub fn fold<R, F: FnMut(&T, &[R]) -> R>(&self, start: usize, mut f: F) -> R {
+		// In post-order, the results of a node's children are the last ones on the stack
+		let mut results = Vec::new();
+		for (i, value) in self.iter_post_order(start) {
+			let children = results.len() - self.child_count(i);
+			let result = f(value, &results[children..]);
+			results.truncate(children);
+			results.push(result);
+		}
+		results.pop().expect("Heap: Internal Error: Fold produced no result.")
+	}
+	/// Like `map`, but borrows the heap. `NodeId`s are valid in both heaps, while weak ids
+	/// and the observer stay with `self`, as they do for a clone.
+	pub fn map_ref<U, F: FnMut(&T) -> U>(&self, mut f: F) -> Heap<U> {
+		let mut inner = self.sibling_nodes(self.inner.len());
+		inner.extend(self.inner.iter().map(|node| node.with_value(node.value.as_ref().map(&mut f))));
+		Heap {
+			inner,
+			free: self.free.clone(),
+			len: self.len,
+			min_generation: self.min_generation,
+			watchers: Watchers::default(),
+			observer: Observer::default(),
+			pre_order: Default::default(),
+		}
+	}
+}
+
+impl<T, E> Heap<Result<T, E>> {
+	/// Returns the heap of every `Ok` value, or the error of the lowest valid index holding one
+	pub fn transpose(self) -> Result<Heap<T>, E> {
+		self.try_map(|value| value)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::format;
+	use alloc::string::String;
+	use alloc::vec::Vec;
+
+	use crate::tree;
+
+	#[test]
+	fn map_test() {
+		let mut heap = tree!("root" => ["a" => ["a1"], "b"]);
+		heap.remove(2);
+		let id = heap.id_of(3).unwrap();
+		let lengths = heap.map_ref(|value| value.len());
+		assert_eq!(lengths.iter().copied().collect::<Vec<_>>(), vec![4, 1, 1]);
+		assert_eq!(lengths.resolve(id), Some(3));
+		assert_eq!(lengths.children(0).collect::<Vec<_>>(), heap.children(0).collect::<Vec<_>>());
+		let weak = heap.weak_id(3);
+		let mut owned = heap.map(String::from);
+		owned[3].push('!');
+		assert_eq!(owned[3], "b!");
+		assert!(!owned.is_valid_idx(2));
+		assert_eq!(owned.insert(String::from("c"), 1), 2);
+		owned.remove(3);
+		assert!(weak.is_removed());
+	}
+
+	#[test]
+	fn fold_test() {
+		#[derive(Debug)]
+		enum Expr {
+			Num(i64),
+			Add,
+			Mul,
+		}
+		let heap = tree!(Expr::Add => [Expr::Num(1), Expr::Mul => [Expr::Num(2), Expr::Num(3), Expr::Num(4)]]);
+		let eval = |expr: &Expr, args: &[i64]| match expr {
+			Expr::Num(n) => *n,
+			Expr::Add => args.iter().sum(),
+			Expr::Mul => args.iter().product(),
+		};
+		assert_eq!(heap.fold(0, eval), 25);
+		assert_eq!(heap.fold(2, eval), 24);
+		let shape = heap.fold(0, |expr, children: &[String]| match expr {
+			Expr::Num(n) => format!("{n}"),
+			_ => format!("({})", children.join(" ")),
+		});
+		assert_eq!(shape, "(1 (2 3 4))");
+	}
+
+	#[test]
+	fn try_map_test() {
+		let heap = tree!("1" => ["2" => ["x"], "3", "y"]);
+		let parsed = heap.clone().try_map(|value| value.parse::<u8>());
+		assert!(parsed.is_err());
+		let mut visited = Vec::new();
+		let failed = heap.clone().try_map(|value| {
+			visited.push(value);
+			value.parse::<u8>().map_err(|_| value)
+		});
+		assert_eq!(failed.unwrap_err(), "x");
+		assert_eq!(visited, vec!["1", "2", "x"]);
+		let mut digits = heap.clone();
+		digits.remove(2);
+		digits.remove(4);
+		let digits = digits.try_map(|value| value.parse::<u8>()).unwrap();
+		assert_eq!(digits.iter().copied().collect::<Vec<_>>(), vec![1, 2, 3]);
+		assert_eq!(heap.map(|value| value.parse::<u8>().map_err(|_| value)).transpose().unwrap_err(), "x");
+		let ok = tree!(Ok::<_, ()>(0) => [Ok(1)]).transpose().unwrap();
+		assert_eq!(ok, tree!(0 => [1]));
+	}
+}
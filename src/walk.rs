@@ -0,0 +1,132 @@
+use core::ops::ControlFlow;
+
+use crate::Heap;
+
+/// What [`Heap::walk`] should do after entering a node
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalkControl<B> {
+	/// Go on to the node's children
+	Continue,
+	/// Leave the node right away, without visiting its descendants
+	SkipSubtree,
+	/// Stop the walk, returning the value from `walk`
+	Break(B),
+}
+
+/// Callbacks for [`Heap::walk`]. Both methods continue the walk by default.
+pub trait Visitor<T> {
+	type Break;
+	/// Called before any of the node's descendants are visited
+	fn enter(&mut self, _index: usize, _value: &T) -> WalkControl<Self::Break> {
+		WalkControl::Continue
+	}
+	/// Called after all of the node's descendants have been visited or skipped
+	fn leave(&mut self, _index: usize, _value: &T) -> ControlFlow<Self::Break> {
+		ControlFlow::Continue(())
+	}
+}
+
+impl<T> Heap<T> {
+	/// Visits the subtree rooted at `start` depth-first, entering every node before its
+	/// children and leaving it after them. Returns the value of the first `Break`, or `None`
+	/// if the walk finished.
+	/// Panics if `start` is invalid
+	pub fn walk<V: Visitor<T>>(&self, start: usize, visitor: &mut V) -> Option<V::Break> {
+		assert!(self.is_valid_idx(start), "Heap: Error: Tried to traverse from an invalid node");
+		let mut current = start;
+		loop {
+			match visitor.enter(current, &self[current]) {
+				WalkControl::Break(value) => return Some(value),
+				WalkControl::Continue => {
+					if let Some(child) = self.inner[current].first_child.get() {
+						current = child;
+						continue;
+					}
+				}
+				WalkControl::SkipSubtree => (),
+			}
+			// Leave every node that has no more children to visit
+			loop {
+				if let ControlFlow::Break(value) = visitor.leave(current, &self[current]) {
+					return Some(value);
+				}
+				if current == start {
+					return None;
+				}
+				if let Some(sibling) = self.inner[current].next_sibling.get() {
+					current = sibling;
+					break;
+				}
+				current = self.inner[current].parent().expect("Heap: Internal Error: Orphaned node.");
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use alloc::string::String;
+	use alloc::vec::Vec;
+	use core::ops::ControlFlow;
+
+	use super::{Visitor, WalkControl};
+	use crate::tree;
+
+	struct Printer {
+		out: String,
+		skip: &'static str,
+		stop: &'static str,
+	}
+
+	impl Visitor<&'static str> for Printer {
+		type Break = usize;
+		fn enter(&mut self, index: usize, value: &&'static str) -> WalkControl<usize> {
+			if *value == self.stop {
+				return WalkControl::Break(index);
+			}
+			self.out.push('(');
+			self.out.push_str(value);
+			match *value == self.skip {
+				true => WalkControl::SkipSubtree,
+				false => WalkControl::Continue,
+			}
+		}
+		fn leave(&mut self, _: usize, _: &&'static str) -> ControlFlow<usize> {
+			self.out.push(')');
+			ControlFlow::Continue(())
+		}
+	}
+
+	#[test]
+	fn walk_test() {
+		let heap = tree!("r" => ["a" => ["a1", "a2"], "b" => ["b1"], "c"]);
+		let mut printer = Printer { out: String::new(), skip: "a", stop: "" };
+		assert_eq!(heap.walk(0, &mut printer), None);
+		assert_eq!(printer.out, "(r(a)(b(b1))(c))");
+		let mut printer = Printer { out: String::new(), skip: "", stop: "b1" };
+		assert_eq!(heap.walk(0, &mut printer), Some(5));
+		assert_eq!(printer.out, "(r(a(a1)(a2))(b");
+		let mut printer = Printer { out: String::new(), skip: "", stop: "c" };
+		assert_eq!(heap.walk(1, &mut printer), None);
+		assert_eq!(printer.out, "(a(a1)(a2))");
+	}
+
+	#[test]
+	fn walk_leave_break_test() {
+		struct Deepest(Vec<usize>);
+		impl Visitor<i32> for Deepest {
+			type Break = usize;
+			fn leave(&mut self, index: usize, value: &i32) -> ControlFlow<usize> {
+				self.0.push(index);
+				match *value < 0 {
+					true => ControlFlow::Break(index),
+					false => ControlFlow::Continue(()),
+				}
+			}
+		}
+		let heap = tree!(0 => [1 => [2, -3], 4]);
+		let mut visitor = Deepest(Vec::new());
+		assert_eq!(heap.walk(0, &mut visitor), Some(3));
+		assert_eq!(visitor.0, vec![2, 3]);
+	}
+}
@@ -0,0 +1,129 @@
+use alloc::sync::{Arc, Weak};
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{Heap, Map};
+
+// Stored in a handle's shared index once its node has been removed
+const REMOVED: usize = usize::MAX;
+
+/// A handle that is told when its node is removed, and follows the node through `compact`
+/// and `reorder_dfs`. Created by [`Heap::weak_id`].
+/// Unlike a [`NodeId`](crate::NodeId), it can be checked without access to the heap, such as
+/// from a UI layer that only holds on to a selection.
+#[derive(Clone)]
+pub struct WeakNodeId {
+	index: Arc<AtomicUsize>,
+}
+
+impl WeakNodeId {
+	/// Returns the node's current index, or `None` once it has been removed
+	pub fn index(&self) -> Option<usize> {
+		match self.index.load(Ordering::Acquire) {
+			REMOVED => None,
+			index => Some(index),
+		}
+	}
+	pub fn is_removed(&self) -> bool {
+		self.index().is_none()
+	}
+}
+
+impl fmt::Debug for WeakNodeId {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("WeakNodeId").field(&self.index()).finish()
+	}
+}
+
+// The shared index of every outstanding `WeakNodeId`, by node
+#[derive(Default)]
+pub(crate) struct Watchers(Map<usize, Weak<AtomicUsize>>);
+
+impl Watchers {
+	pub(crate) fn removed(&mut self, index: usize) {
+		if let Some(shared) = self.0.remove(&index).and_then(|weak| weak.upgrade()) {
+			shared.store(REMOVED, Ordering::Release);
+		}
+	}
+	// Moves every handle to its node's new index, dropping entries no handle refers to anymore
+	pub(crate) fn remap(&mut self, new_index: impl Fn(usize) -> usize) {
+		let old = core::mem::take(&mut self.0);
+		for (index, weak) in old {
+			if let Some(shared) = weak.upgrade() {
+				let index = new_index(index);
+				shared.store(index, Ordering::Release);
+				self.0.insert(index, weak);
+			}
+		}
+	}
+	// Takes over the handles of another heap whose nodes were moved into this one
+	pub(crate) fn adopt(&mut self, other: Watchers, new_index: impl Fn(usize) -> usize) {
+		for (index, weak) in other.0 {
+			if let Some(shared) = weak.upgrade() {
+				let index = new_index(index);
+				shared.store(index, Ordering::Release);
+				self.0.insert(index, weak);
+			}
+		}
+	}
+}
+
+// A cloned heap has its own nodes, which the original's handles must not follow
+impl Clone for Watchers {
+	fn clone(&self) -> Self {
+		Self::default()
+	}
+}
+
+impl fmt::Debug for Watchers {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_set().entries(self.0.keys()).finish()
+	}
+}
+
+impl<T> Heap<T> {
+	/// Returns a handle that reports when the node at `index` is removed. Nodes moved into
+	/// another heap by `split_off` count as removed.
+	/// Panics if `index` is invalid
+	pub fn weak_id(&mut self, index: usize) -> WeakNodeId {
+		assert!(self.is_valid_idx(index), "Heap: Error: Tried to watch an invalid node");
+		let weak = self.watchers.0.entry(index).or_default();
+		let shared = weak.upgrade().unwrap_or_else(|| {
+			let shared = Arc::new(AtomicUsize::new(index));
+			*weak = Arc::downgrade(&shared);
+			shared
+		});
+		WeakNodeId { index: shared }
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use crate::Heap;
+
+	#[test]
+	fn weak_id_test() {
+		let mut heap = Heap::new("root");
+		let a = heap.insert("a", 0);
+		let b = heap.insert("b", 0);
+		let b1 = heap.insert("b1", b);
+		let weak_a = heap.weak_id(a);
+		let weak_b1 = heap.weak_id(b1);
+		let again = heap.weak_id(b1);
+		heap.remove(a);
+		assert!(weak_a.is_removed());
+		assert_eq!(heap.insert("reused", 0), a);
+		assert!(weak_a.is_removed());
+		heap.remove(a);
+		heap.compact();
+		assert_eq!(weak_b1.index(), Some(2));
+		assert_eq!(heap[again.index().unwrap()], "b1");
+		heap.reorder_dfs(|_, _| ());
+		assert_eq!(weak_b1.index(), Some(2));
+		let mut copy = heap.clone();
+		copy.remove(1);
+		assert!(!weak_b1.is_removed());
+		heap.split_off(1);
+		assert!(again.is_removed());
+	}
+}